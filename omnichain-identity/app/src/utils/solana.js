@@ -1,5 +1,6 @@
 import { PublicKey, SystemProgram, Transaction } from '@solana/web3.js';
 import { Buffer } from 'buffer';
+import { getAddress } from 'ethers';
 
 // Replace with your actual program ID after deployment
 export const IDENTITY_PROGRAM_ID = new PublicKey('DDyBRUnarV5xAdTn3XmjbhEGuiinCBRLT1tGkc33f5Fz');
@@ -26,24 +27,70 @@ export const getLinkedAddresses = async (connection, solanaPublicKey) => {
       return [];
     }
     
-    // Deserialize the account data (this is a simplified version)
-    // In a real implementation, you'd use a proper deserializer based on your account structure
+    // Deserialize the account data:
+    // discriminator (8) | authority (32) | linked_addresses: Vec<LinkedAddress> | bump
+    // where LinkedAddress = ChainAddress | chain_reference: Option<String> | verification
     const data = accountInfo.data;
-    
-    // Skip the authority field (32 bytes) and read the vector of strings
-    const linkedAddressesCount = data.readUInt32LE(32);
-    
+    const linkedAddressesCount = data.readUInt32LE(40);
+
     const linkedAddresses = [];
-    let offset = 36; // 32 (authority) + 4 (count)
-    
-    for (let i = 0; i < linkedAddressesCount; i++) {
+    let offset = 44; // 8 (discriminator) + 32 (authority) + 4 (count)
+
+    const skipBytes = () => {
       const length = data.readUInt32LE(offset);
-      offset += 4;
-      
-      const address = data.slice(offset, offset + length).toString();
-      linkedAddresses.push(address);
-      
-      offset += length;
+      offset += 4 + length;
+    };
+
+    for (let i = 0; i < linkedAddressesCount; i++) {
+      const namespace = data.readUInt8(offset);
+      offset += 1;
+
+      switch (namespace) {
+        case 0: {
+          // Evm: 20 bytes, shown EIP-55 checksummed like wallets do
+          const address = `0x${data.slice(offset, offset + 20).toString('hex')}`;
+          linkedAddresses.push(getAddress(address));
+          offset += 20;
+          break;
+        }
+        case 1: // Aptos
+        case 2: // Sui
+          offset += 32;
+          break;
+        case 3: // Cosmos { hrp, data }
+          skipBytes();
+          skipBytes();
+          break;
+        case 4: {
+          // Bitcoin: Base58 { version, hash } or Segwit { hrp, version, program }
+          const kind = data.readUInt8(offset);
+          offset += 1;
+          if (kind === 0) {
+            offset += 21;
+          } else {
+            skipBytes();
+            offset += 1;
+            skipBytes();
+          }
+          break;
+        }
+        default:
+          throw new Error(`Unknown address namespace ${namespace}`);
+      }
+
+      // chain_reference: Option<String>
+      const hasChainReference = data.readUInt8(offset);
+      offset += 1;
+      if (hasChainReference) {
+        skipBytes();
+      }
+
+      // verification: SelfAsserted, or Attested / Downgraded { attestor_id: u32 }
+      const verification = data.readUInt8(offset);
+      offset += 1;
+      if (verification !== 0) {
+        offset += 4;
+      }
     }
     
     return linkedAddresses;
@@ -151,7 +198,7 @@ export const addLinkedAddress = async (connection, wallet, evmAddress) => {
 export const isAddressLinked = async (connection, solanaPublicKey, evmAddress) => {
   try {
     const linkedAddresses = await getLinkedAddresses(connection, solanaPublicKey);
-    return linkedAddresses.includes(evmAddress);
+    return linkedAddresses.includes(getAddress(evmAddress));
   } catch (error) {
     console.error('Error checking if address is linked:', error);
     return false;
@@ -7,7 +7,8 @@ import "@layerzerolabs/oapp-evm/contracts/oapp/libs/OptionsBuilder.sol";
 /**
  * @title OmnichainIdentityLinker
  * @dev Contract for linking EVM addresses to Solana addresses via LayerZero V2
- * This contract sends messages from EVM chains to a Solana OApp
+ * This contract sends messages from EVM chains to a Solana OApp, answers links
+ * queries from Solana and can ask Solana whether a link exists
  */
 contract OmnichainIdentityLinker is OApp {
     using OptionsBuilder for bytes;
@@ -17,12 +18,54 @@ contract OmnichainIdentityLinker is OApp {
     // Gas limit for cross-chain calls
     uint256 public gasLimit = 200000;
 
+    // Typed messages start with their type, see identity_core::query on Solana
+    uint8 public constant MSG_LINKS_QUERY = 1;
+    uint8 public constant MSG_LINKS_RESPONSE = 2;
+    uint8 public constant MSG_VERIFY_QUERY = 3;
+    uint8 public constant MSG_VERIFY_RESPONSE = 4;
+    uint8 public constant MSG_LINK_BATCH = 5;
+    uint8 public constant MSG_AUTHORITY_ROTATION = 7;
+
+    // Most links linkAddresses can send in one message
+    uint256 public constant MAX_BATCH_LINKS = 5;
+
+    // Verification levels reported by Solana for a link
+    uint8 public constant LEVEL_NOT_LINKED = 0;
+    uint8 public constant LEVEL_SELF_ASSERTED = 1;
+    uint8 public constant LEVEL_ATTESTED = 2;
+    uint8 public constant LEVEL_DOWNGRADED = 3;
+    uint8 public constant LEVEL_IMPORTED = 4;
+
+    // Responses to Solana queries list at most this many addresses, to fit in a Solana transaction
+    uint256 public constant MAX_QUERY_RESULTS = 8;
+
+    // Compute units given to the Solana lz_receive that processes query responses
+    uint128 public responseComputeUnits = 200000;
+
     // Store linked addresses history for reference
     mapping(address => bytes[]) public linkedSolanaAddresses;
 
     // Event emitted when a link is created
     event IdentityLinked(address evmAddress, bytes solanaAddress, uint256 timestamp);
 
+    // Event emitted when a linked identity moved to a new Solana wallet
+    event SolanaAddressRotated(address indexed evmAddress, bytes oldSolanaAddress, bytes newSolanaAddress);
+
+    // Answer from Solana to a verifyLink query
+    struct LinkVerification {
+        address evmAddress;
+        bytes32 solanaAddress;
+        bool answered;
+        uint8 level;     // One of the LEVEL_ constants
+        uint32 levelId;  // Attestor id for attested and downgraded links, import id for imported ones
+    }
+
+    // Verification queries sent to Solana, by guid
+    mapping(bytes32 => LinkVerification) public linkVerifications;
+
+    event LinkVerificationRequested(bytes32 indexed guid, address evmAddress, bytes32 solanaAddress);
+    event LinkVerified(bytes32 indexed guid, address evmAddress, bytes32 solanaAddress, uint8 level, uint32 levelId);
+
     constructor(address _lzEndpoint, address _delegate) OApp(_lzEndpoint, _delegate) {}
 
     /**
@@ -67,6 +110,95 @@ contract OmnichainIdentityLinker is OApp {
         );
     }
     
+    /**
+     * @dev Ask to link the sender's EVM address to several Solana addresses with a single message
+     * Solana handles each link on its own and reports the outcomes in events. The message only
+     * proves control of the sender's address, so Solana holds each link as pending until the
+     * authority of the identity accepts it; frozen identities and identities with too many
+     * pending links turn the request down.
+     * @param _solanaAddresses Solana addresses as strings
+     */
+    function linkAddresses(string[] calldata _solanaAddresses) external payable {
+        _linkAddresses(_solanaAddresses, 0);
+    }
+
+    /**
+     * @dev Same as linkAddresses, also asking Solana to make the sender's address the primary
+     * address of some of the identities, once their authority accepts the link
+     * @param _solanaAddresses Solana addresses as strings
+     * @param _primaryMask Bit i set makes the sender primary for _solanaAddresses[i]
+     */
+    function linkAddressesWithPrimary(string[] calldata _solanaAddresses, uint8 _primaryMask) external payable {
+        _linkAddresses(_solanaAddresses, _primaryMask);
+    }
+
+    function _linkAddresses(string[] calldata _solanaAddresses, uint8 _primaryMask) internal {
+        bytes memory payload = _linkBatchPayload(msg.sender, _solanaAddresses, _primaryMask);
+
+        for (uint256 i = 0; i < _solanaAddresses.length; i++) {
+            linkedSolanaAddresses[msg.sender].push(bytes(_solanaAddresses[i]));
+            emit IdentityLinked(msg.sender, bytes(_solanaAddresses[i]), block.timestamp);
+        }
+
+        bytes memory options = OptionsBuilder.newOptions()
+            .addExecutorLzReceiveOption(uint128(gasLimit), 0);
+        _lzSend(
+            SOLANA_CHAIN_ID,
+            payload,
+            options,
+            MessagingFee(msg.value, 0),
+            payable(msg.sender)
+        );
+    }
+
+    /**
+     * @dev Quote the fee for linkAddresses
+     * @param _solanaAddresses Solana addresses as strings
+     * @return Fee required for the cross-chain message
+     */
+    function quoteLinkAddresses(string[] calldata _solanaAddresses) external view returns (uint256) {
+        return _quoteLinkAddresses(_solanaAddresses, 0);
+    }
+
+    /**
+     * @dev Quote the fee for linkAddressesWithPrimary
+     * @param _solanaAddresses Solana addresses as strings
+     * @param _primaryMask Bit i set makes the sender primary for _solanaAddresses[i]
+     * @return Fee required for the cross-chain message
+     */
+    function quoteLinkAddressesWithPrimary(string[] calldata _solanaAddresses, uint8 _primaryMask) external view returns (uint256) {
+        return _quoteLinkAddresses(_solanaAddresses, _primaryMask);
+    }
+
+    function _quoteLinkAddresses(string[] calldata _solanaAddresses, uint8 _primaryMask) internal view returns (uint256) {
+        bytes memory payload = _linkBatchPayload(msg.sender, _solanaAddresses, _primaryMask);
+        bytes memory options = OptionsBuilder.newOptions()
+            .addExecutorLzReceiveOption(uint128(gasLimit), 0);
+        return _quote(SOLANA_CHAIN_ID, payload, options, false).nativeFee;
+    }
+
+    /**
+     * @dev Batch: type (1) | timestamp (8) | count (1) | count x [evmAddress (20) | len (1) | solanaAddress]
+     * [| primaryMask (1)], the mask only being there when it is not zero
+     */
+    function _linkBatchPayload(
+        address _evmAddress,
+        string[] calldata _solanaAddresses,
+        uint8 _primaryMask
+    ) internal view returns (bytes memory) {
+        require(_solanaAddresses.length > 0 && _solanaAddresses.length <= MAX_BATCH_LINKS, "Invalid batch size");
+        bytes memory payload = abi.encodePacked(MSG_LINK_BATCH, uint64(block.timestamp), uint8(_solanaAddresses.length));
+        for (uint256 i = 0; i < _solanaAddresses.length; i++) {
+            require(bytes(_solanaAddresses[i]).length <= type(uint8).max, "Solana address too long");
+            payload = abi.encodePacked(payload, _evmAddress, uint8(bytes(_solanaAddresses[i]).length), _solanaAddresses[i]);
+        }
+        if (_primaryMask != 0) {
+            require(uint256(_primaryMask) >> _solanaAddresses.length == 0, "Invalid primary mask");
+            payload = abi.encodePacked(payload, _primaryMask);
+        }
+        return payload;
+    }
+
     /**
      * @dev Convert an address to a string
      * @param _addr The address to convert
@@ -188,18 +320,177 @@ contract OmnichainIdentityLinker is OApp {
     }
 
     /**
-     * @dev Override the _lzReceive function to handle incoming messages (if needed)
-     * This would be used if you want to receive messages from Solana back to EVM
+     * @dev Ask Solana whether an EVM address is linked to a Solana wallet
+     * The answer arrives later through _lzReceive and is stored in linkVerifications
+     * under the returned guid. Solana sends it back with _responseFee lamports,
+     * which the executor forwards through the lzReceive value, so msg.value must
+     * cover both directions.
+     * @param _evmAddress EVM address to check
+     * @param _solanaAddress Solana wallet, as its 32 byte public key
+     * @param _chainId EIP-155 chain the link must cover, 0 for any chain
+     * @param _responseFee Native fee in lamports for the response from Solana
+     * @return guid Identifier of the query
+     */
+    function verifyLink(
+        address _evmAddress,
+        bytes32 _solanaAddress,
+        uint64 _chainId,
+        uint64 _responseFee
+    ) external payable returns (bytes32 guid) {
+        bytes memory payload = _verifyQueryPayload(_evmAddress, _solanaAddress, _chainId, _responseFee);
+        bytes memory options = _verifyQueryOptions(_responseFee);
+
+        MessagingReceipt memory receipt = _lzSend(
+            SOLANA_CHAIN_ID,
+            payload,
+            options,
+            MessagingFee(msg.value, 0),
+            payable(msg.sender)
+        );
+        guid = receipt.guid;
+        linkVerifications[guid] = LinkVerification(_evmAddress, _solanaAddress, false, LEVEL_NOT_LINKED, 0);
+        emit LinkVerificationRequested(guid, _evmAddress, _solanaAddress);
+    }
+
+    /**
+     * @dev Quote the fee for verifyLink
+     * @return fee The estimated messaging fee for the query
+     */
+    function quoteVerifyLink(
+        address _evmAddress,
+        bytes32 _solanaAddress,
+        uint64 _chainId,
+        uint64 _responseFee
+    ) external view returns (MessagingFee memory fee) {
+        bytes memory payload = _verifyQueryPayload(_evmAddress, _solanaAddress, _chainId, _responseFee);
+        return _quote(SOLANA_CHAIN_ID, payload, _verifyQueryOptions(_responseFee), false);
+    }
+
+    /**
+     * @dev Query: type (1) | evmAddress (20) | solanaAddress (32) | chainId (8) | responseFee (8)
+     */
+    function _verifyQueryPayload(
+        address _evmAddress,
+        bytes32 _solanaAddress,
+        uint64 _chainId,
+        uint64 _responseFee
+    ) internal pure returns (bytes memory) {
+        return abi.encodePacked(MSG_VERIFY_QUERY, _evmAddress, _solanaAddress, _chainId, _responseFee);
+    }
+
+    function _verifyQueryOptions(uint64 _responseFee) internal view returns (bytes memory) {
+        return OptionsBuilder.newOptions().addExecutorLzReceiveOption(uint128(gasLimit), uint128(_responseFee));
+    }
+
+    /**
+     * @dev Handle typed messages from the Solana OApp
+     * Links queries are answered right away, paid for with the native value the
+     * querier attached to the message through its executor options.
      */
     function _lzReceive(
         Origin calldata _origin,
         bytes32 _guid,
         bytes calldata _payload,
-        address _executor,
-        bytes calldata _extraData
+        address /*_executor*/,
+        bytes calldata /*_extraData*/
     ) internal override {
-        // Add handling for received messages from Solana if needed
-        // For now, this is a placeholder
+        require(_payload.length > 0, "Empty payload");
+        uint8 msgType = uint8(_payload[0]);
+        if (msgType == MSG_LINKS_QUERY) {
+            _answerLinksQuery(_origin.srcEid, _guid, _payload);
+        } else if (msgType == MSG_VERIFY_RESPONSE) {
+            _recordLinkVerification(_payload);
+        } else if (msgType == MSG_AUTHORITY_ROTATION) {
+            _rotateSolanaAddress(_payload);
+        } else {
+            revert("Unsupported message type");
+        }
+    }
+
+    /**
+     * @dev Send the Solana addresses an EVM account linked back to the querier
+     * Query: type (1) | evmAddress (20) | composeTo (32)
+     * Response: type (1) | queryGuid (32) | composeTo (32) | evmAddress (20) | count (2) | count x [len (1) | address]
+     */
+    function _answerLinksQuery(uint32 _srcEid, bytes32 _guid, bytes calldata _payload) internal {
+        require(_payload.length == 53, "Invalid links query");
+        address evmAddress = address(bytes20(_payload[1:21]));
+        bytes32 composeTo = bytes32(_payload[21:53]);
+
+        bytes[] storage linked = linkedSolanaAddresses[evmAddress];
+        uint256 count = linked.length < MAX_QUERY_RESULTS ? linked.length : MAX_QUERY_RESULTS;
+        bytes memory response = abi.encodePacked(MSG_LINKS_RESPONSE, _guid, composeTo, evmAddress, uint16(count));
+        for (uint256 i = 0; i < count; i++) {
+            response = abi.encodePacked(response, uint8(linked[i].length), linked[i]);
+        }
+
+        bytes memory options = OptionsBuilder.newOptions()
+            .addExecutorLzReceiveOption(responseComputeUnits, 0);
+        _lzSend(
+            _srcEid,
+            response,
+            options,
+            MessagingFee(msg.value, 0),
+            payable(owner())     // Refund address for any excess
+        );
+    }
+
+    /**
+     * @dev Rotation: type (1) | len (1) | old Solana address | len (1) | new Solana address
+     * | count (1) | count x evmAddress (20). Replaces the old address in the links of each EVM address.
+     */
+    function _rotateSolanaAddress(bytes calldata _payload) internal {
+        uint256 offset = 1;
+        uint256 oldLength = uint8(_payload[offset]);
+        bytes calldata oldAddress = _payload[offset + 1:offset + 1 + oldLength];
+        offset += 1 + oldLength;
+        uint256 newLength = uint8(_payload[offset]);
+        bytes calldata newAddress = _payload[offset + 1:offset + 1 + newLength];
+        offset += 1 + newLength;
+        uint256 count = uint8(_payload[offset]);
+        offset += 1;
+        require(_payload.length == offset + count * 20, "Invalid authority rotation");
+
+        bytes32 oldHash = keccak256(oldAddress);
+        for (uint256 i = 0; i < count; i++) {
+            address evmAddress = address(bytes20(_payload[offset + i * 20:offset + (i + 1) * 20]));
+            bytes[] storage linked = linkedSolanaAddresses[evmAddress];
+            for (uint256 j = 0; j < linked.length; j++) {
+                if (keccak256(linked[j]) == oldHash) {
+                    linked[j] = newAddress;
+                    emit SolanaAddressRotated(evmAddress, oldAddress, newAddress);
+                }
+            }
+        }
+    }
+
+    /**
+     * @dev Store the answer to a verifyLink query
+     * Response: type (1) | queryGuid (32) | evmAddress (20) | solanaAddress (32) | level (1) | levelId (4)
+     */
+    function _recordLinkVerification(bytes calldata _payload) internal {
+        require(_payload.length == 90, "Invalid verify response");
+        bytes32 queryGuid = bytes32(_payload[1:33]);
+        LinkVerification storage verification = linkVerifications[queryGuid];
+        require(
+            verification.evmAddress == address(bytes20(_payload[33:53]))
+                && verification.solanaAddress == bytes32(_payload[53:85]),
+            "Unknown verify query"
+        );
+        require(!verification.answered, "Already answered");
+
+        verification.answered = true;
+        verification.level = uint8(_payload[85]);
+        verification.levelId = uint32(bytes4(_payload[86:90]));
+        emit LinkVerified(queryGuid, verification.evmAddress, verification.solanaAddress, verification.level, verification.levelId);
+    }
+
+    /**
+     * @dev Update the compute units given to Solana for query responses
+     * @param _computeUnits New compute unit limit
+     */
+    function setResponseComputeUnits(uint128 _computeUnits) external onlyOwner {
+        responseComputeUnits = _computeUnits;
     }
 
     /**
@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import { MessagingParams, MessagingFee, MessagingReceipt, Origin } from "@layerzerolabs/lz-evm-protocol-v2/contracts/interfaces/ILayerZeroEndpointV2.sol";
+import { ILayerZeroReceiver } from "@layerzerolabs/lz-evm-protocol-v2/contracts/interfaces/ILayerZeroReceiver.sol";
+
+/**
+ * @title MockLzEndpoint
+ * @dev Stand-in for the LayerZero EndpointV2 in tests. Sends are recorded and
+ * announced with MessageSent instead of going anywhere, and deliver plays the
+ * part of the executor to hand an inbound message to an OApp.
+ */
+contract MockLzEndpoint {
+    uint256 public nativeFee;
+    uint64 public nonce;
+
+    event MessageSent(uint32 dstEid, bytes32 receiver, bytes32 guid, bytes message, bytes options, uint256 value);
+
+    function setDelegate(address /*_delegate*/) external {}
+
+    function setNativeFee(uint256 _nativeFee) external {
+        nativeFee = _nativeFee;
+    }
+
+    function quote(MessagingParams calldata /*_params*/, address /*_sender*/) external view returns (MessagingFee memory) {
+        return MessagingFee(nativeFee, 0);
+    }
+
+    function send(
+        MessagingParams calldata _params,
+        address /*_refundAddress*/
+    ) external payable returns (MessagingReceipt memory receipt) {
+        require(msg.value >= nativeFee, "Insufficient fee");
+        nonce++;
+        bytes32 guid = keccak256(abi.encodePacked(nonce, msg.sender, _params.dstEid, _params.receiver));
+        emit MessageSent(_params.dstEid, _params.receiver, guid, _params.message, _params.options, msg.value);
+        return MessagingReceipt(guid, nonce, MessagingFee(msg.value, 0));
+    }
+
+    function deliver(
+        address _receiver,
+        Origin calldata _origin,
+        bytes32 _guid,
+        bytes calldata _message
+    ) external payable {
+        ILayerZeroReceiver(_receiver).lzReceive{ value: msg.value }(_origin, _guid, _message, msg.sender, "");
+    }
+}
@@ -1,5 +1,7 @@
 const { expect } = require("chai");
 const { ethers } = require("hardhat");
+const { time } = require("@nomicfoundation/hardhat-network-helpers");
+const { anyValue } = require("@nomicfoundation/hardhat-chai-matchers/withArgs");
 
 describe("OmnichainIdentityLinker", function () {
   let identityLinker;
@@ -11,6 +13,11 @@ describe("OmnichainIdentityLinker", function () {
   const SOLANA_ADDRESS = ethers.toUtf8Bytes("SolanaAddress123456789");
   const DESTINATION_ADDRESS = ethers.zeroPadValue("0x1111111111111111111111111111111111111111", 32);
 
+  const SOLANA_EID = 40168;
+  const SOLANA_PEER = ethers.zeroPadValue("0x2222222222222222222222222222222222222222", 32);
+  const WALLET_A = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+  const WALLET_B = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
   beforeEach(async function () {
     // Get signers
     [owner, user] = await ethers.getSigners();
@@ -21,9 +28,29 @@ describe("OmnichainIdentityLinker", function () {
     
     // Deploy the OmnichainIdentityLinker contract
     const OmnichainIdentityLinker = await ethers.getContractFactory("OmnichainIdentityLinker");
-    identityLinker = await OmnichainIdentityLinker.deploy(await mockLzEndpoint.getAddress());
+    identityLinker = await OmnichainIdentityLinker.deploy(await mockLzEndpoint.getAddress(), owner.address);
+    await identityLinker.setPeer(SOLANA_EID, SOLANA_PEER);
   });
 
+  // Messages sent through the mock endpoint, in order
+  async function sentMessages(tx) {
+    const receipt = await (await tx).wait();
+    return receipt.logs
+      .filter((log) => log.address === mockLzEndpoint.target)
+      .map((log) => mockLzEndpoint.interface.parseLog(log).args);
+  }
+
+  // Hand a message from the Solana OApp to the linker, as the executor would
+  function deliver(message, { guid = ethers.id("inbound"), sender = SOLANA_PEER, value = 0 } = {}) {
+    return mockLzEndpoint.deliver(
+      identityLinker.target,
+      { srcEid: SOLANA_EID, sender, nonce: 1 },
+      guid,
+      message,
+      { value }
+    );
+  }
+
   describe("Deployment", function () {
     it("Should set the right owner", async function () {
       expect(await identityLinker.owner()).to.equal(owner.address);
@@ -88,4 +115,206 @@ describe("OmnichainIdentityLinker", function () {
       expect(finalBalance).to.be.gt(initialBalance);
     });
   });
+
+  describe("Batch linking", function () {
+    function batchPayload(timestamp, evmAddress, wallets, primaryMask = 0) {
+      const types = ["uint8", "uint64", "uint8"];
+      const values = [5, timestamp, wallets.length];
+      for (const wallet of wallets) {
+        types.push("address", "uint8", "string");
+        values.push(evmAddress, ethers.toUtf8Bytes(wallet).length, wallet);
+      }
+      if (primaryMask !== 0) {
+        types.push("uint8");
+        values.push(primaryMask);
+      }
+      return ethers.solidityPacked(types, values);
+    }
+
+    it("Should send every link in one message", async function () {
+      const tx = identityLinker.connect(user).linkAddresses([WALLET_A, WALLET_B]);
+      const [sent] = await sentMessages(tx);
+
+      expect(sent.dstEid).to.equal(SOLANA_EID);
+      expect(sent.receiver).to.equal(SOLANA_PEER);
+      expect(sent.message).to.equal(batchPayload(await time.latest(), user.address, [WALLET_A, WALLET_B]));
+      expect(await identityLinker.getLinkedAddressesAsStrings(user.address)).to.deep.equal([WALLET_A, WALLET_B]);
+    });
+
+    it("Should append the primary mask only when it is set", async function () {
+      const tx = identityLinker.connect(user).linkAddressesWithPrimary([WALLET_A, WALLET_B], 0b10);
+      const [sent] = await sentMessages(tx);
+      expect(sent.message).to.equal(batchPayload(await time.latest(), user.address, [WALLET_A, WALLET_B], 0b10));
+
+      const [unmasked] = await sentMessages(identityLinker.connect(user).linkAddressesWithPrimary([WALLET_A], 0));
+      expect(unmasked.message).to.equal(batchPayload(await time.latest(), user.address, [WALLET_A]));
+    });
+
+    it("Should emit an event per link", async function () {
+      await expect(identityLinker.connect(user).linkAddresses([WALLET_A, WALLET_B]))
+        .to.emit(identityLinker, "IdentityLinked")
+        .withArgs(user.address, ethers.toUtf8Bytes(WALLET_B), anyValue);
+    });
+
+    it("Should reject empty and oversized batches", async function () {
+      await expect(identityLinker.connect(user).linkAddresses([])).to.be.revertedWith("Invalid batch size");
+      const wallets = Array(6).fill(WALLET_A);
+      await expect(identityLinker.connect(user).linkAddresses(wallets)).to.be.revertedWith("Invalid batch size");
+    });
+
+    it("Should reject a primary mask beyond the batch", async function () {
+      await expect(
+        identityLinker.connect(user).linkAddressesWithPrimary([WALLET_A, WALLET_B], 0b100)
+      ).to.be.revertedWith("Invalid primary mask");
+    });
+  });
+
+  describe("Links query", function () {
+    const COMPOSE_TO = ethers.zeroPadValue("0x3333", 32);
+
+    function linksQuery(evmAddress) {
+      return ethers.solidityPacked(["uint8", "address", "bytes32"], [1, evmAddress, COMPOSE_TO]);
+    }
+
+    it("Should answer with the linked Solana addresses", async function () {
+      await identityLinker.connect(user).linkAddresses([WALLET_A, WALLET_B]);
+      const guid = ethers.id("links query");
+
+      const [sent] = await sentMessages(deliver(linksQuery(user.address), { guid, value: 1000 }));
+
+      expect(sent.dstEid).to.equal(SOLANA_EID);
+      expect(sent.value).to.equal(1000);
+      expect(sent.message).to.equal(
+        ethers.solidityPacked(
+          ["uint8", "bytes32", "bytes32", "address", "uint16", "uint8", "string", "uint8", "string"],
+          [2, guid, COMPOSE_TO, user.address, 2, WALLET_A.length, WALLET_A, WALLET_B.length, WALLET_B]
+        )
+      );
+    });
+
+    it("Should answer with no addresses for an unknown EVM address", async function () {
+      const guid = ethers.id("links query");
+      const [sent] = await sentMessages(deliver(linksQuery(owner.address), { guid }));
+      expect(sent.message).to.equal(
+        ethers.solidityPacked(["uint8", "bytes32", "bytes32", "address", "uint16"], [2, guid, COMPOSE_TO, owner.address, 0])
+      );
+    });
+
+    it("Should reject malformed queries", async function () {
+      await expect(deliver(linksQuery(user.address) + "00")).to.be.revertedWith("Invalid links query");
+    });
+
+    it("Should only take messages from the Solana peer", async function () {
+      await expect(deliver(linksQuery(user.address), { sender: DESTINATION_ADDRESS }))
+        .to.be.revertedWithCustomError(identityLinker, "OnlyPeer");
+      await expect(
+        identityLinker.lzReceive({ srcEid: SOLANA_EID, sender: SOLANA_PEER, nonce: 1 }, ethers.id("x"), linksQuery(user.address), owner.address, "0x")
+      ).to.be.revertedWithCustomError(identityLinker, "OnlyEndpoint");
+    });
+  });
+
+  describe("verifyLink", function () {
+    const SOLANA_KEY = ethers.id("solana wallet");
+    const CHAIN_ID = 1;
+    const RESPONSE_FEE = 5000;
+
+    async function requestVerification() {
+      const receipt = await (await identityLinker.verifyLink(user.address, SOLANA_KEY, CHAIN_ID, RESPONSE_FEE)).wait();
+      const [sent] = receipt.logs
+        .filter((log) => log.address === mockLzEndpoint.target)
+        .map((log) => mockLzEndpoint.interface.parseLog(log).args);
+      return sent;
+    }
+
+    function verifyResponse(guid, evmAddress, level, levelId) {
+      return ethers.solidityPacked(
+        ["uint8", "bytes32", "address", "bytes32", "uint8", "uint32"],
+        [4, guid, evmAddress, SOLANA_KEY, level, levelId]
+      );
+    }
+
+    it("Should send the query and record it under its guid", async function () {
+      const sent = await requestVerification();
+
+      expect(sent.message).to.equal(
+        ethers.solidityPacked(
+          ["uint8", "address", "bytes32", "uint64", "uint64"],
+          [3, user.address, SOLANA_KEY, CHAIN_ID, RESPONSE_FEE]
+        )
+      );
+      const verification = await identityLinker.linkVerifications(sent.guid);
+      expect(verification.evmAddress).to.equal(user.address);
+      expect(verification.solanaAddress).to.equal(SOLANA_KEY);
+      expect(verification.answered).to.equal(false);
+    });
+
+    it("Should store the answer from Solana", async function () {
+      const { guid } = await requestVerification();
+      const LEVEL_ATTESTED = await identityLinker.LEVEL_ATTESTED();
+
+      await expect(deliver(verifyResponse(guid, user.address, LEVEL_ATTESTED, 7)))
+        .to.emit(identityLinker, "LinkVerified")
+        .withArgs(guid, user.address, SOLANA_KEY, LEVEL_ATTESTED, 7);
+
+      const verification = await identityLinker.linkVerifications(guid);
+      expect(verification.answered).to.equal(true);
+      expect(verification.level).to.equal(LEVEL_ATTESTED);
+      expect(verification.levelId).to.equal(7);
+    });
+
+    it("Should reject a second answer", async function () {
+      const { guid } = await requestVerification();
+      await deliver(verifyResponse(guid, user.address, 1, 0));
+      await expect(deliver(verifyResponse(guid, user.address, 2, 7))).to.be.revertedWith("Already answered");
+    });
+
+    it("Should reject answers to unknown queries", async function () {
+      const { guid } = await requestVerification();
+      await expect(deliver(verifyResponse(guid, owner.address, 1, 0))).to.be.revertedWith("Unknown verify query");
+      await expect(deliver(verifyResponse(ethers.id("other"), user.address, 1, 0))).to.be.revertedWith(
+        "Unknown verify query"
+      );
+      await expect(deliver(verifyResponse(guid, user.address, 1, 0) + "00")).to.be.revertedWith(
+        "Invalid verify response"
+      );
+    });
+  });
+
+  describe("Authority rotation", function () {
+    const NEW_WALLET = "GqzLmUEcn6WhCBvaxu1bXs6WbRqAjwgbDQcBgJbi1Ba8";
+
+    function rotation(oldWallet, newWallet, evmAddresses) {
+      return ethers.solidityPacked(
+        ["uint8", "uint8", "string", "uint8", "string", "uint8", ...evmAddresses.map(() => "address")],
+        [7, oldWallet.length, oldWallet, newWallet.length, newWallet, evmAddresses.length, ...evmAddresses]
+      );
+    }
+
+    it("Should replace the old wallet in the links of each EVM address", async function () {
+      await identityLinker.connect(user).linkAddresses([WALLET_A, WALLET_B]);
+      await identityLinker.connect(owner).linkAddresses([WALLET_A]);
+
+      await expect(deliver(rotation(WALLET_A, NEW_WALLET, [user.address, owner.address])))
+        .to.emit(identityLinker, "SolanaAddressRotated")
+        .withArgs(user.address, ethers.toUtf8Bytes(WALLET_A), ethers.toUtf8Bytes(NEW_WALLET));
+
+      expect(await identityLinker.getLinkedAddressesAsStrings(user.address)).to.deep.equal([NEW_WALLET, WALLET_B]);
+      expect(await identityLinker.getLinkedAddressesAsStrings(owner.address)).to.deep.equal([NEW_WALLET]);
+    });
+
+    it("Should leave EVM addresses without the old wallet alone", async function () {
+      await identityLinker.connect(user).linkAddresses([WALLET_B]);
+      await expect(deliver(rotation(WALLET_A, NEW_WALLET, [user.address]))).not.to.emit(
+        identityLinker,
+        "SolanaAddressRotated"
+      );
+      expect(await identityLinker.getLinkedAddressesAsStrings(user.address)).to.deep.equal([WALLET_B]);
+    });
+
+    it("Should reject malformed rotations", async function () {
+      await expect(deliver(rotation(WALLET_A, NEW_WALLET, [user.address]) + "00")).to.be.revertedWith(
+        "Invalid authority rotation"
+      );
+    });
+  });
 });
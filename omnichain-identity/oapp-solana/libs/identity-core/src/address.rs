@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+use crate::errors::IdentityError;
+
+// -----------------------------------------------------------------------------
+// EVM addresses are stored in one canonical form: "0x" followed by 40
+// lowercase hex characters, which is also what `OmnichainIdentityLinker`
+// emits. Mixed-case input must carry a valid EIP-55 checksum, since a wrong
+// checksum almost always means a typo. The checksummed form is only produced
+// for display.
+// -----------------------------------------------------------------------------
+
+// Check if the string is a valid EVM address (0x followed by 40 hex chars).
+// Mixed-case addresses must also match their EIP-55 checksum.
+pub fn is_valid_evm_address(address: &str) -> bool {
+    if !address.starts_with("0x") || address.len() != 42 {
+        return false;
+    }
+
+    let hex = &address[2..];
+    for c in hex.chars() {
+        if !c.is_ascii_hexdigit() {
+            return false;
+        }
+    }
+
+    // All-lowercase and all-uppercase addresses carry no checksum
+    let has_lower = hex.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = hex.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return address == to_checksum_address(address);
+    }
+
+    true
+}
+
+// Validate an EVM address and return its canonical (lowercase) form
+pub fn canonicalize_evm_address(address: &str) -> Result<String> {
+    if !is_valid_evm_address(address) {
+        msg!("Invalid EVM address: {}", address);
+        return Err(error!(IdentityError::InvalidAddress));
+    }
+    Ok(address.to_ascii_lowercase())
+}
+
+// Compare two EVM addresses regardless of how they are cased
+pub fn evm_addresses_equal(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+// Format an address with its EIP-55 checksum, the way wallets display it.
+// The input is expected to already be a well-formed 0x-prefixed address.
+pub fn to_checksum_address(address: &str) -> String {
+    let lower = address[2..].to_ascii_lowercase();
+    let hash = keccak::hash(lower.as_bytes()).to_bytes();
+
+    let mut checksummed = String::with_capacity(42);
+    checksummed.push_str("0x");
+    for (i, c) in lower.chars().enumerate() {
+        // Each hex character is checked against the matching nibble of the hash
+        let nibble = if i % 2 == 0 { hash[i / 2] >> 4 } else { hash[i / 2] & 0x0f };
+        if c.is_ascii_alphabetic() && nibble >= 8 {
+            checksummed.push(c.to_ascii_uppercase());
+        } else {
+            checksummed.push(c);
+        }
+    }
+    checksummed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors from EIP-55
+    const CHECKSUMMED: [&str; 4] = [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+
+    #[test]
+    fn checksums_match_eip55_vectors() {
+        for address in CHECKSUMMED {
+            assert_eq!(to_checksum_address(&address.to_ascii_lowercase()), address);
+            assert!(is_valid_evm_address(address));
+        }
+    }
+
+    #[test]
+    fn rejects_a_wrong_checksum() {
+        // Same address as the first vector with one letter's case flipped
+        assert!(!is_valid_evm_address("0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+    }
+
+    #[test]
+    fn accepts_single_case_addresses_without_checksum() {
+        let lower = CHECKSUMMED[0].to_ascii_lowercase();
+        let upper = format!("0x{}", CHECKSUMMED[0][2..].to_ascii_uppercase());
+        assert!(is_valid_evm_address(&lower));
+        assert!(is_valid_evm_address(&upper));
+    }
+
+    #[test]
+    fn rejects_malformed_addresses() {
+        assert!(!is_valid_evm_address("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"));
+        assert!(!is_valid_evm_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1bea"));
+        assert!(!is_valid_evm_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaeg"));
+    }
+
+    #[test]
+    fn canonical_form_is_lowercase() {
+        assert_eq!(
+            canonicalize_evm_address(CHECKSUMMED[1]).unwrap(),
+            CHECKSUMMED[1].to_ascii_lowercase()
+        );
+        assert!(canonicalize_evm_address("0x1234").is_err());
+        assert!(evm_addresses_equal(CHECKSUMMED[2], &CHECKSUMMED[2].to_ascii_lowercase()));
+    }
+}
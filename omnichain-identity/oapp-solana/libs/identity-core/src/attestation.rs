@@ -0,0 +1,160 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{ed25519_program, instruction::Instruction};
+
+use crate::errors::IdentityError;
+
+// -----------------------------------------------------------------------------
+// Link attestations are ed25519 signatures by a trusted attestor over the
+// message built by `attestation_message`. They are checked by introspecting
+// the Ed25519 program instruction placed in the same transaction, so the
+// signature itself is verified by the runtime.
+// -----------------------------------------------------------------------------
+
+// Domain separator so attestation signatures can't be confused with other messages
+pub const ATTESTATION_DOMAIN: &[u8] = b"omnichain-identity:attest-link:v2";
+
+// Layout of the Ed25519 program instruction data (see solana-sdk ed25519_instruction)
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_SIZE: usize = 14;
+const PUBKEY_SIZE: usize = 32;
+const SIGNATURE_SIZE: usize = 64;
+
+// Message an attestor signs to vouch that `account_id` belongs to `authority`.
+// `program_id` pins the attestation to one deployment. `nonce` is the identity's
+// attestation nonce, which each use of an attestation bumps, so an attestation
+// can't be replayed, e.g. after the authority removed the link. `expires_at`
+// bounds how long an unused attestation stays valid.
+pub fn attestation_message(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    nonce: u64,
+    account_id: &str,
+    expires_at: i64,
+) -> Vec<u8> {
+    let mut message =
+        Vec::with_capacity(ATTESTATION_DOMAIN.len() + 32 + 32 + 8 + 4 + account_id.len() + 8);
+    message.extend_from_slice(ATTESTATION_DOMAIN);
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(authority.as_ref());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&(account_id.len() as u32).to_le_bytes());
+    message.extend_from_slice(account_id.as_bytes());
+    message.extend_from_slice(&expires_at.to_le_bytes());
+    message
+}
+
+// Check that `ix` is an Ed25519 program instruction verifying exactly one
+// signature by `signer` over `message`, with all data inlined in `ix` itself.
+pub fn verify_ed25519_instruction(ix: &Instruction, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    if ix.program_id != ed25519_program::ID || !ix.accounts.is_empty() {
+        return Err(error!(IdentityError::InvalidSignature));
+    }
+    let data = &ix.data;
+    if data.len() < SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_SIZE || data[0] != 1 {
+        return Err(error!(IdentityError::InvalidSignature));
+    }
+
+    let offsets = &data[SIGNATURE_OFFSETS_START..SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_SIZE];
+    let read_u16 = |i: usize| u16::from_le_bytes([offsets[i], offsets[i + 1]]);
+    let signature_offset = read_u16(0) as usize;
+    let signature_instruction_index = read_u16(2);
+    let public_key_offset = read_u16(4) as usize;
+    let public_key_instruction_index = read_u16(6);
+    let message_data_offset = read_u16(8) as usize;
+    let message_data_size = read_u16(10) as usize;
+    let message_instruction_index = read_u16(12);
+
+    // Every part must live in the Ed25519 instruction itself
+    if [signature_instruction_index, public_key_instruction_index, message_instruction_index]
+        .iter()
+        .any(|index| *index != u16::MAX)
+    {
+        return Err(error!(IdentityError::InvalidSignature));
+    }
+
+    let slice = |offset: usize, len: usize| data.get(offset..offset.checked_add(len)?);
+    let signed_key = slice(public_key_offset, PUBKEY_SIZE);
+    let signed_message = slice(message_data_offset, message_data_size);
+    if slice(signature_offset, SIGNATURE_SIZE).is_none()
+        || signed_key != Some(signer.as_ref())
+        || signed_message != Some(message)
+    {
+        return Err(error!(IdentityError::InvalidSignature));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Ed25519 instruction data with the key, signature and message inlined, in
+    // the layout produced by `new_ed25519_instruction`
+    fn ed25519_ix(signer: &Pubkey, message: &[u8]) -> Instruction {
+        let public_key_offset = SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_SIZE;
+        let signature_offset = public_key_offset + PUBKEY_SIZE;
+        let message_data_offset = signature_offset + SIGNATURE_SIZE;
+        let mut data = vec![1, 0];
+        for value in [
+            signature_offset as u16,
+            u16::MAX,
+            public_key_offset as u16,
+            u16::MAX,
+            message_data_offset as u16,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[7; SIGNATURE_SIZE]);
+        data.extend_from_slice(message);
+        Instruction { program_id: ed25519_program::ID, accounts: vec![], data }
+    }
+
+    #[test]
+    fn message_binds_every_field() {
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let account_id = "eip155:1:0xab16a96D359eC26a11e2C2b3d8f8B8942d5Bfcdb";
+        let message = attestation_message(&program_id, &authority, 3, account_id, 1_700_000_000);
+
+        let mut expected = ATTESTATION_DOMAIN.to_vec();
+        expected.extend_from_slice(program_id.as_ref());
+        expected.extend_from_slice(authority.as_ref());
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(&(account_id.len() as u32).to_le_bytes());
+        expected.extend_from_slice(account_id.as_bytes());
+        expected.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        assert_eq!(message, expected);
+
+        // A used up nonce gives another message
+        assert_ne!(
+            message,
+            attestation_message(&program_id, &authority, 4, account_id, 1_700_000_000)
+        );
+    }
+
+    #[test]
+    fn verifies_the_inlined_signer_and_message() {
+        let signer = Pubkey::new_unique();
+        let ix = ed25519_ix(&signer, b"message");
+        assert!(verify_ed25519_instruction(&ix, &signer, b"message").is_ok());
+        assert!(verify_ed25519_instruction(&ix, &Pubkey::new_unique(), b"message").is_err());
+        assert!(verify_ed25519_instruction(&ix, &signer, b"other").is_err());
+    }
+
+    #[test]
+    fn rejects_data_from_other_instructions() {
+        let signer = Pubkey::new_unique();
+        let mut ix = ed25519_ix(&signer, b"message");
+        // Message instruction index pointing at instruction 0
+        ix.data[SIGNATURE_OFFSETS_START + 12..SIGNATURE_OFFSETS_START + 14]
+            .copy_from_slice(&[0, 0]);
+        assert!(verify_ed25519_instruction(&ix, &signer, b"message").is_err());
+
+        let mut ix = ed25519_ix(&signer, b"message");
+        ix.program_id = Pubkey::new_unique();
+        assert!(verify_ed25519_instruction(&ix, &signer, b"message").is_err());
+    }
+}
@@ -0,0 +1,190 @@
+// -----------------------------------------------------------------------------
+// Minimal bech32 / bech32m codec (BIP-173 and BIP-350), used to validate and
+// format Cosmos and Bitcoin segwit addresses. Only what the address
+// validators need is implemented.
+// -----------------------------------------------------------------------------
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc830a3;
+
+// Longest string allowed by BIP-173
+const MAX_LENGTH: usize = 90;
+// Checksum length in characters
+const CHECKSUM_LENGTH: usize = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(self) -> u32 {
+        match self {
+            Variant::Bech32 => BECH32_CONST,
+            Variant::Bech32m => BECH32M_CONST,
+        }
+    }
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut chk: u32 = 1;
+    for v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ (*v as u32);
+        for (i, g) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut expanded = Vec::with_capacity(hrp.len() * 2 + 1);
+    expanded.extend(hrp.bytes().map(|c| c >> 5));
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|c| c & 31));
+    expanded
+}
+
+// Decode a bech32 or bech32m string into its lowercase hrp and 5-bit data
+// (checksum stripped). Returns None on any malformed input.
+pub fn decode(input: &str) -> Option<(String, Vec<u8>, Variant)> {
+    if input.len() < 8 || input.len() > MAX_LENGTH {
+        return None;
+    }
+    // Mixed case is never valid
+    let has_lower = input.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = input.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return None;
+    }
+    let input = input.to_ascii_lowercase();
+
+    let separator = input.rfind('1')?;
+    if separator == 0 || separator + CHECKSUM_LENGTH + 1 > input.len() {
+        return None;
+    }
+    let (hrp, data) = (&input[..separator], &input[separator + 1..]);
+    if hrp.bytes().any(|c| !(33..=126).contains(&c)) {
+        return None;
+    }
+
+    let mut values = Vec::with_capacity(data.len());
+    for c in data.bytes() {
+        values.push(CHARSET.iter().position(|x| *x == c)? as u8);
+    }
+
+    let mut checked = hrp_expand(hrp);
+    checked.extend_from_slice(&values);
+    let variant = match polymod(&checked) {
+        BECH32_CONST => Variant::Bech32,
+        BECH32M_CONST => Variant::Bech32m,
+        _ => return None,
+    };
+
+    values.truncate(values.len() - CHECKSUM_LENGTH);
+    Some((hrp.to_string(), values, variant))
+}
+
+// Encode a lowercase hrp and 5-bit data into a bech32 or bech32m string
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> String {
+    let mut checked = hrp_expand(hrp);
+    checked.extend_from_slice(data);
+    checked.extend_from_slice(&[0u8; CHECKSUM_LENGTH]);
+    let checksum = polymod(&checked) ^ variant.constant();
+
+    let mut encoded = String::with_capacity(hrp.len() + 1 + data.len() + CHECKSUM_LENGTH);
+    encoded.push_str(hrp);
+    encoded.push('1');
+    for v in data {
+        encoded.push(CHARSET[*v as usize] as char);
+    }
+    for i in 0..CHECKSUM_LENGTH {
+        encoded.push(CHARSET[((checksum >> (5 * (5 - i))) & 31) as usize] as char);
+    }
+    encoded
+}
+
+// Regroup bits, e.g. 8-bit bytes into 5-bit bech32 values and back
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max_value: u32 = (1 << to) - 1;
+    let mut converted = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for value in data {
+        let value = *value as u32;
+        if value >> from != 0 {
+            return None;
+        }
+        acc = (acc << from) | value;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            converted.push(((acc >> bits) & max_value) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            converted.push(((acc << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max_value) != 0 {
+        return None;
+    }
+    Some(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_bip173_vectors() {
+        for valid in [
+            "A12UEL5L",
+            "a12uel5l",
+            "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw",
+            "split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w",
+        ] {
+            let (_, _, variant) = decode(valid).unwrap_or_else(|| panic!("{}", valid));
+            assert_eq!(variant, Variant::Bech32);
+        }
+    }
+
+    #[test]
+    fn decodes_bip350_vectors() {
+        for valid in ["A1LQFN3A", "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx"] {
+            let (_, _, variant) = decode(valid).unwrap_or_else(|| panic!("{}", valid));
+            assert_eq!(variant, Variant::Bech32m);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_strings() {
+        // No separator, empty hrp, mixed case, bad checksum
+        for invalid in ["pzry9x0s0muk", "1pzry9x0s0muk", "A12uEL5L", "a12uel5m"] {
+            assert!(decode(invalid).is_none(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn encode_round_trips_with_decode() {
+        let data = convert_bits(&[0x00, 0x14, 0x75, 0x1e, 0x76, 0xe8], 8, 5, true).unwrap();
+        for variant in [Variant::Bech32, Variant::Bech32m] {
+            let encoded = encode("test", &data, variant);
+            assert_eq!(decode(&encoded), Some(("test".to_string(), data.clone(), variant)));
+        }
+    }
+
+    #[test]
+    fn convert_bits_rejects_non_zero_padding() {
+        let values = convert_bits(&[0xff], 8, 5, true).unwrap();
+        assert_eq!(values, vec![31, 28]);
+        assert_eq!(convert_bits(&values, 5, 8, false), Some(vec![0xff]));
+        assert_eq!(convert_bits(&[31, 29], 5, 8, false), None);
+    }
+}
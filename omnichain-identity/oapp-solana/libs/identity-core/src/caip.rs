@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    chain_address::{AddressNamespace, ChainAddress},
+    errors::IdentityError,
+    link::LinkedAddress,
+};
+
+// -----------------------------------------------------------------------------
+// CAIP-10 account identifiers: "<namespace>:<reference>:<address>", where
+// "<namespace>:<reference>" is a CAIP-2 chain id, e.g.
+//   eip155:1:0xab16a96D359eC26a11e2C2b3d8f8B8942d5Bfcdb
+//   cosmos:cosmoshub-4:cosmos1t2uflqwqe0fsj0shcfkrvpukewcw40yjj6hdc0
+//   bip122:000000000019d6689c085ae165831e93:128Lkh3S7CkDTBZ8W7BbpsN3YYizJMp8p6
+//
+// A link is either scoped to one chain, or chain agnostic (an EOA valid on
+// every EVM chain, for instance). Chain agnostic links use the reserved
+// reference "0", so "eip155:0:0xabc..." means "0xabc... on any EVM chain".
+// -----------------------------------------------------------------------------
+
+// Reference used for chain agnostic links
+pub const CHAIN_AGNOSTIC_REFERENCE: &str = "0";
+// CAIP-2 bounds: namespace is [-a-z0-9]{3,8}, reference is [-_a-zA-Z0-9]{1,32}
+pub const MAX_CAIP2_NAMESPACE_LENGTH: usize = 8;
+pub const MAX_CHAIN_REFERENCE_LENGTH: usize = 32;
+
+// CAIP-2 namespace used for each address namespace
+pub fn caip2_namespace(namespace: AddressNamespace) -> &'static str {
+    match namespace {
+        AddressNamespace::Evm => "eip155",
+        AddressNamespace::Aptos => "aptos",
+        AddressNamespace::Sui => "sui",
+        AddressNamespace::Cosmos => "cosmos",
+        AddressNamespace::Bitcoin => "bip122",
+    }
+}
+
+fn address_namespace(caip2_namespace: &str) -> Result<AddressNamespace> {
+    match caip2_namespace {
+        "eip155" => Ok(AddressNamespace::Evm),
+        "aptos" => Ok(AddressNamespace::Aptos),
+        "sui" => Ok(AddressNamespace::Sui),
+        "cosmos" => Ok(AddressNamespace::Cosmos),
+        "bip122" => Ok(AddressNamespace::Bitcoin),
+        _ => Err(error!(IdentityError::UnsupportedNamespace)),
+    }
+}
+
+fn is_valid_caip2_namespace(namespace: &str) -> bool {
+    (3..=MAX_CAIP2_NAMESPACE_LENGTH).contains(&namespace.len())
+        && namespace.bytes().all(|c| c == b'-' || c.is_ascii_lowercase() || c.is_ascii_digit())
+}
+
+pub fn is_valid_chain_reference(reference: &str) -> bool {
+    (1..=MAX_CHAIN_REFERENCE_LENGTH).contains(&reference.len())
+        && reference.bytes().all(|c| c == b'-' || c == b'_' || c.is_ascii_alphanumeric())
+}
+
+// Parse a CAIP-10 account id into a link. The address is validated with the
+// rules of its namespace, and the reserved reference makes the link chain agnostic.
+pub fn parse_account_id(account_id: &str) -> Result<LinkedAddress> {
+    let mut parts = account_id.splitn(3, ':');
+    let (namespace, reference, address) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(namespace), Some(reference), Some(address)) => (namespace, reference, address),
+        _ => {
+            msg!("Invalid CAIP-10 account id: {}", account_id);
+            return Err(error!(IdentityError::InvalidAccountId));
+        },
+    };
+    if !is_valid_caip2_namespace(namespace) || !is_valid_chain_reference(reference) {
+        msg!("Invalid CAIP-2 chain id: {}:{}", namespace, reference);
+        return Err(error!(IdentityError::InvalidAccountId));
+    }
+
+    let address = ChainAddress::parse(address_namespace(namespace)?, address)?;
+    let chain_reference =
+        if reference == CHAIN_AGNOSTIC_REFERENCE { None } else { Some(reference.to_string()) };
+    Ok(LinkedAddress::new(address, chain_reference))
+}
+
+// Format a link as a CAIP-10 account id
+pub fn format_account_id(link: &LinkedAddress) -> String {
+    format!(
+        "{}:{}:{}",
+        caip2_namespace(link.address.namespace()),
+        link.chain_reference.as_deref().unwrap_or(CHAIN_AGNOSTIC_REFERENCE),
+        link.address
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_address::BitcoinAddress;
+
+    #[test]
+    fn round_trips_caip10_examples() {
+        for account_id in [
+            "eip155:1:0xab16a96D359eC26a11e2C2b3d8f8B8942d5Bfcdb",
+            "cosmos:cosmoshub-4:cosmos1t2uflqwqe0fsj0shcfkrvpukewcw40yjj6hdc0",
+            "bip122:000000000019d6689c085ae165831e93:128Lkh3S7CkDTBZ8W7BbpsN3YYizJMp8p6",
+        ] {
+            let link = parse_account_id(account_id).unwrap();
+            assert_eq!(format_account_id(&link), account_id);
+        }
+    }
+
+    #[test]
+    fn parses_scoped_and_chain_agnostic_links() {
+        let scoped =
+            parse_account_id("eip155:1:0xab16a96D359eC26a11e2C2b3d8f8B8942d5Bfcdb").unwrap();
+        assert_eq!(scoped.chain_reference.as_deref(), Some("1"));
+        assert!(matches!(scoped.address, ChainAddress::Evm(_)));
+
+        let agnostic =
+            parse_account_id("eip155:0:0xab16a96D359eC26a11e2C2b3d8f8B8942d5Bfcdb").unwrap();
+        assert_eq!(agnostic.chain_reference, None);
+        assert!(agnostic.covers(&scoped));
+        assert!(scoped.covers(&agnostic));
+
+        let other_chain =
+            parse_account_id("eip155:10:0xab16a96D359eC26a11e2C2b3d8f8B8942d5Bfcdb").unwrap();
+        assert!(!scoped.covers(&other_chain));
+    }
+
+    #[test]
+    fn validates_the_address_with_its_namespace() {
+        let link = parse_account_id(
+            "bip122:000000000019d6689c085ae165831e93:bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+        )
+        .unwrap();
+        assert!(matches!(link.address, ChainAddress::Bitcoin(BitcoinAddress::Segwit { .. })));
+
+        // Bad EIP-55 checksum, and an EVM address under the cosmos namespace
+        assert!(parse_account_id("eip155:1:0xab16a96D359eC26a11e2C2b3d8f8B8942d5BfcdB").is_err());
+        assert!(parse_account_id("cosmos:cosmoshub-4:0xab16a96D359eC26a11e2C2b3d8f8B8942d5Bfcdb")
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_account_ids() {
+        let address = "0xab16a96D359eC26a11e2C2b3d8f8B8942d5Bfcdb";
+        for account_id in [
+            address.to_string(),
+            format!("eip155:{}", address),
+            format!("ei:1:{}", address),
+            format!("EIP155:1:{}", address),
+            format!("eip155::{}", address),
+            format!("eip155:1.0:{}", address),
+            format!("eip155:{}:{}", "1".repeat(MAX_CHAIN_REFERENCE_LENGTH + 1), address),
+            format!("solana:1:{}", address),
+        ] {
+            assert!(parse_account_id(&account_id).is_err(), "{}", account_id);
+        }
+    }
+}
@@ -0,0 +1,391 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{
+    address::{is_valid_evm_address, to_checksum_address},
+    bech32,
+    errors::IdentityError,
+};
+
+// -----------------------------------------------------------------------------
+// Linked addresses are stored as a `ChainAddress`: the namespace the address
+// belongs to plus its validated raw bytes. Every namespace has its own
+// validator, and `Display` turns the bytes back into the form wallets show.
+//
+// | Namespace | Accepted input                       | Stored as              |
+// |-----------|--------------------------------------|------------------------|
+// | evm       | 0x + 40 hex, EIP-55 if mixed case    | 20 bytes               |
+// | aptos     | 0x + up to 64 hex                    | 32 bytes (left padded) |
+// | sui       | 0x + up to 64 hex                    | 32 bytes (left padded) |
+// | cosmos    | bech32, 20 or 32 byte payload        | hrp + payload          |
+// | bitcoin   | base58check P2PKH/P2SH, bech32(m)    | version + hash/program |
+// -----------------------------------------------------------------------------
+
+// Longest bech32 human readable part we store (e.g. "cosmos", "osmo", "celestia")
+pub const MAX_HRP_LENGTH: usize = 16;
+// BIP-141 bounds the witness program to 40 bytes
+pub const MAX_WITNESS_PROGRAM_LENGTH: usize = 40;
+
+// Base58check version bytes for mainnet/testnet P2PKH and P2SH
+const BITCOIN_BASE58_VERSIONS: [u8; 4] = [0x00, 0x05, 0x6f, 0xc4];
+// Human readable parts for mainnet and testnet segwit addresses
+const BITCOIN_HRPS: [&str; 2] = ["bc", "tb"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum AddressNamespace {
+    Evm,
+    Aptos,
+    Sui,
+    Cosmos,
+    Bitcoin,
+}
+
+impl AddressNamespace {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AddressNamespace::Evm => "evm",
+            AddressNamespace::Aptos => "aptos",
+            AddressNamespace::Sui => "sui",
+            AddressNamespace::Cosmos => "cosmos",
+            AddressNamespace::Bitcoin => "bitcoin",
+        }
+    }
+}
+
+impl FromStr for AddressNamespace {
+    type Err = Error;
+
+    fn from_str(namespace: &str) -> Result<Self> {
+        match namespace {
+            "evm" => Ok(AddressNamespace::Evm),
+            "aptos" => Ok(AddressNamespace::Aptos),
+            "sui" => Ok(AddressNamespace::Sui),
+            "cosmos" => Ok(AddressNamespace::Cosmos),
+            "bitcoin" => Ok(AddressNamespace::Bitcoin),
+            _ => Err(error!(IdentityError::UnsupportedNamespace)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum BitcoinAddress {
+    // Legacy base58check address; the version byte selects network and P2PKH/P2SH
+    Base58 { version: u8, hash: [u8; 20] },
+    // Native segwit address; bech32 for witness v0, bech32m for v1 and above
+    Segwit { hrp: String, version: u8, program: Vec<u8> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum ChainAddress {
+    Evm([u8; 20]),
+    Aptos([u8; 32]),
+    Sui([u8; 32]),
+    Cosmos { hrp: String, data: Vec<u8> },
+    Bitcoin(BitcoinAddress),
+}
+
+impl ChainAddress {
+    // Largest serialized variant is a segwit Bitcoin address
+    pub const MAX_SIZE: usize = 1 +                 // ChainAddress tag
+        1 +                                         // BitcoinAddress tag
+        (4 + MAX_HRP_LENGTH) +                      // hrp
+        1 +                                         // witness version
+        (4 + MAX_WITNESS_PROGRAM_LENGTH); // witness program
+
+    pub fn namespace(&self) -> AddressNamespace {
+        match self {
+            ChainAddress::Evm(_) => AddressNamespace::Evm,
+            ChainAddress::Aptos(_) => AddressNamespace::Aptos,
+            ChainAddress::Sui(_) => AddressNamespace::Sui,
+            ChainAddress::Cosmos { .. } => AddressNamespace::Cosmos,
+            ChainAddress::Bitcoin(_) => AddressNamespace::Bitcoin,
+        }
+    }
+
+    // Validate an address with the validator of its namespace
+    pub fn parse(namespace: AddressNamespace, address: &str) -> Result<Self> {
+        let parsed = match namespace {
+            AddressNamespace::Evm => parse_evm(address).map(ChainAddress::Evm),
+            AddressNamespace::Aptos => parse_move_address(address).map(ChainAddress::Aptos),
+            AddressNamespace::Sui => parse_move_address(address).map(ChainAddress::Sui),
+            AddressNamespace::Cosmos => {
+                parse_cosmos(address).map(|(hrp, data)| ChainAddress::Cosmos { hrp, data })
+            },
+            AddressNamespace::Bitcoin => parse_bitcoin(address).map(ChainAddress::Bitcoin),
+        };
+        parsed.ok_or_else(|| {
+            msg!("Invalid {} address: {}", namespace.as_str(), address);
+            error!(IdentityError::InvalidAddress)
+        })
+    }
+}
+
+impl fmt::Display for ChainAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainAddress::Evm(bytes) => {
+                write!(f, "{}", to_checksum_address(&format!("0x{}", encode_hex(bytes))))
+            },
+            ChainAddress::Aptos(bytes) | ChainAddress::Sui(bytes) => {
+                write!(f, "0x{}", encode_hex(bytes))
+            },
+            ChainAddress::Cosmos { hrp, data } => {
+                let values = bech32::convert_bits(data, 8, 5, true).ok_or(fmt::Error)?;
+                write!(f, "{}", bech32::encode(hrp, &values, bech32::Variant::Bech32))
+            },
+            ChainAddress::Bitcoin(BitcoinAddress::Base58 { version, hash }) => {
+                let mut payload = Vec::with_capacity(25);
+                payload.push(*version);
+                payload.extend_from_slice(hash);
+                let checksum = base58_checksum(&payload);
+                payload.extend_from_slice(&checksum);
+                write!(f, "{}", bs58::encode(payload).into_string())
+            },
+            ChainAddress::Bitcoin(BitcoinAddress::Segwit { hrp, version, program }) => {
+                let mut values = vec![*version];
+                values.extend(bech32::convert_bits(program, 8, 5, true).ok_or(fmt::Error)?);
+                let variant =
+                    if *version == 0 { bech32::Variant::Bech32 } else { bech32::Variant::Bech32m };
+                write!(f, "{}", bech32::encode(hrp, &values, variant))
+            },
+        }
+    }
+}
+
+fn parse_evm(address: &str) -> Option<[u8; 20]> {
+    if !is_valid_evm_address(address) {
+        return None;
+    }
+    decode_hex(&address[2..])?.try_into().ok()
+}
+
+// Aptos and Sui both use 32 byte account addresses written as 0x-prefixed hex.
+// Short forms such as "0x1" are left padded with zeros.
+fn parse_move_address(address: &str) -> Option<[u8; 32]> {
+    let hex = address.strip_prefix("0x")?;
+    if hex.is_empty() || hex.len() > 64 {
+        return None;
+    }
+    let padded = format!("{:0>64}", hex);
+    decode_hex(&padded)?.try_into().ok()
+}
+
+fn parse_cosmos(address: &str) -> Option<(String, Vec<u8>)> {
+    let (hrp, values, variant) = bech32::decode(address)?;
+    if variant != bech32::Variant::Bech32 || hrp.len() > MAX_HRP_LENGTH {
+        return None;
+    }
+    // Account addresses are 20 bytes, module and ICA addresses are 32 bytes
+    let data = bech32::convert_bits(&values, 5, 8, false)?;
+    if data.len() != 20 && data.len() != 32 {
+        return None;
+    }
+    Some((hrp, data))
+}
+
+fn parse_bitcoin(address: &str) -> Option<BitcoinAddress> {
+    let lower = address.to_ascii_lowercase();
+    if BITCOIN_HRPS.iter().any(|hrp| lower.starts_with(&format!("{}1", hrp))) {
+        parse_bitcoin_segwit(address)
+    } else {
+        parse_bitcoin_base58(address)
+    }
+}
+
+fn parse_bitcoin_segwit(address: &str) -> Option<BitcoinAddress> {
+    let (hrp, values, variant) = bech32::decode(address)?;
+    if !BITCOIN_HRPS.contains(&hrp.as_str()) {
+        return None;
+    }
+    let (version, rest) = values.split_first()?;
+    if *version > 16 {
+        return None;
+    }
+    let program = bech32::convert_bits(rest, 5, 8, false)?;
+    if program.len() < 2 || program.len() > MAX_WITNESS_PROGRAM_LENGTH {
+        return None;
+    }
+    // BIP-350: v0 uses bech32 and a 20 or 32 byte program, later versions use bech32m
+    let valid = if *version == 0 {
+        variant == bech32::Variant::Bech32 && (program.len() == 20 || program.len() == 32)
+    } else {
+        variant == bech32::Variant::Bech32m
+    };
+    if !valid {
+        return None;
+    }
+    Some(BitcoinAddress::Segwit { hrp, version: *version, program })
+}
+
+fn parse_bitcoin_base58(address: &str) -> Option<BitcoinAddress> {
+    let decoded = bs58::decode(address).into_vec().ok()?;
+    if decoded.len() != 25 {
+        return None;
+    }
+    let (payload, checksum) = decoded.split_at(21);
+    if base58_checksum(payload) != checksum || !BITCOIN_BASE58_VERSIONS.contains(&payload[0]) {
+        return None;
+    }
+    Some(BitcoinAddress::Base58 { version: payload[0], hash: payload[1..].try_into().ok()? })
+}
+
+// First four bytes of the double SHA-256 of the payload
+fn base58_checksum(payload: &[u8]) -> [u8; 4] {
+    let digest = hash(hash(payload).as_ref()).to_bytes();
+    [digest[0], digest[1], digest[2], digest[3]]
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        decode_hex(s).unwrap()
+    }
+
+    fn round_trip(namespace: AddressNamespace, address: &str) -> ChainAddress {
+        let parsed = ChainAddress::parse(namespace, address).unwrap();
+        assert_eq!(parsed.to_string(), address);
+        parsed
+    }
+
+    #[test]
+    fn parses_bitcoin_base58check_addresses() {
+        let p2pkh = round_trip(AddressNamespace::Bitcoin, "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+        assert_eq!(
+            p2pkh,
+            ChainAddress::Bitcoin(BitcoinAddress::Base58 {
+                version: 0x00,
+                hash: hex("62e907b15cbf27d5425399ebf6f0fb50ebb88f18").try_into().unwrap(),
+            })
+        );
+        let p2sh = round_trip(AddressNamespace::Bitcoin, "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy");
+        assert!(matches!(
+            p2sh,
+            ChainAddress::Bitcoin(BitcoinAddress::Base58 { version: 0x05, .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_bad_base58check_addresses() {
+        for invalid in [
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb", // checksum
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfN",  // truncated
+            "LVg2kJoFNg45Nbpy53h7Fe1wKyeXVRhMH9", // litecoin version byte
+        ] {
+            assert!(
+                ChainAddress::parse(AddressNamespace::Bitcoin, invalid).is_err(),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn parses_segwit_addresses() {
+        let v0 = ChainAddress::parse(
+            AddressNamespace::Bitcoin,
+            "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+        )
+        .unwrap();
+        assert_eq!(
+            v0,
+            ChainAddress::Bitcoin(BitcoinAddress::Segwit {
+                hrp: "bc".to_string(),
+                version: 0,
+                program: hex("751e76e8199196d454941c45d1b3a323f1433bd6"),
+            })
+        );
+        assert_eq!(v0.to_string(), "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+
+        let taproot = round_trip(
+            AddressNamespace::Bitcoin,
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+        );
+        assert_eq!(
+            taproot,
+            ChainAddress::Bitcoin(BitcoinAddress::Segwit {
+                hrp: "bc".to_string(),
+                version: 1,
+                program: hex("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_segwit_with_the_wrong_checksum_variant() {
+        let program = bech32::convert_bits(&[0x75; 20], 8, 5, true).unwrap();
+        let mut v0 = vec![0];
+        v0.extend(&program);
+        let v0_bech32m = bech32::encode("bc", &v0, bech32::Variant::Bech32m);
+        assert!(ChainAddress::parse(AddressNamespace::Bitcoin, &v0_bech32m).is_err());
+
+        let program = bech32::convert_bits(&[0x79; 32], 8, 5, true).unwrap();
+        let mut v1 = vec![1];
+        v1.extend(&program);
+        let v1_bech32 = bech32::encode("bc", &v1, bech32::Variant::Bech32);
+        assert!(ChainAddress::parse(AddressNamespace::Bitcoin, &v1_bech32).is_err());
+    }
+
+    #[test]
+    fn parses_cosmos_addresses() {
+        for len in [20, 32] {
+            let data = vec![0xab; len];
+            let values = bech32::convert_bits(&data, 8, 5, true).unwrap();
+            let address = bech32::encode("cosmos", &values, bech32::Variant::Bech32);
+            let parsed = round_trip(AddressNamespace::Cosmos, &address);
+            assert_eq!(parsed, ChainAddress::Cosmos { hrp: "cosmos".to_string(), data });
+        }
+    }
+
+    #[test]
+    fn rejects_bad_cosmos_addresses() {
+        let values = bech32::convert_bits(&[0xab; 20], 8, 5, true).unwrap();
+        let bech32m = bech32::encode("cosmos", &values, bech32::Variant::Bech32m);
+        assert!(ChainAddress::parse(AddressNamespace::Cosmos, &bech32m).is_err());
+
+        let values = bech32::convert_bits(&[0xab; 21], 8, 5, true).unwrap();
+        let wrong_length = bech32::encode("cosmos", &values, bech32::Variant::Bech32);
+        assert!(ChainAddress::parse(AddressNamespace::Cosmos, &wrong_length).is_err());
+    }
+
+    #[test]
+    fn pads_short_move_addresses() {
+        let parsed = ChainAddress::parse(AddressNamespace::Aptos, "0x1").unwrap();
+        let mut expected = [0u8; 32];
+        expected[31] = 1;
+        assert_eq!(parsed, ChainAddress::Aptos(expected));
+        assert_eq!(parsed.to_string(), format!("0x{}1", "0".repeat(63)));
+
+        assert!(ChainAddress::parse(AddressNamespace::Sui, "0x").is_err());
+        assert!(
+            ChainAddress::parse(AddressNamespace::Sui, &format!("0x{}", "1".repeat(65))).is_err()
+        );
+    }
+
+    #[test]
+    fn displays_evm_addresses_checksummed() {
+        let parsed = ChainAddress::parse(
+            AddressNamespace::Evm,
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+        )
+        .unwrap();
+        assert_eq!(parsed.to_string(), "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+    }
+}
@@ -0,0 +1,16 @@
+use anchor_lang::prelude::error_code;
+
+// Error codes shared by every program that links identities. The offset keeps
+// them clear of the program specific errors, which start at the default 6000.
+#[error_code(offset = 6500)]
+pub enum IdentityError {
+    InvalidAddress,
+    InvalidMessageFormat,
+    IdentityAccountNotFound,
+    TooManyLinkedAddresses,
+    UnsupportedNamespace,
+    InvalidAccountId,
+    InvalidSignature,
+    InvalidOptions,
+    InsufficientLzReceiveGas,
+}
@@ -0,0 +1,191 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{keccak, secp256k1_recover::secp256k1_recover};
+
+use crate::errors::IdentityError;
+
+// -----------------------------------------------------------------------------
+// EVM management commands let the linked EVM key chosen as an identity's
+// manager act on the identity without the Solana wallet. The EVM key signs the
+// message built by `evm_command_message` with `personal_sign` (EIP-191), and
+// the program recovers the signer with the `secp256k1_recover` syscall. The
+// identity's nonce is part of the message, so every signature is used once.
+// -----------------------------------------------------------------------------
+
+// Domain separator so command signatures can't be confused with other messages
+pub const EVM_COMMAND_DOMAIN: &[u8] = b"omnichain-identity:evm-command:v1";
+
+// Prefix of EIP-191 version 0x45 messages, followed by the decimal message length
+const PERSONAL_SIGN_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n";
+
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum EvmCommand {
+    // Remove the link for this CAIP-10 account id, exactly as it is linked
+    RemoveLink { account_id: String },
+    // Move the identity to a new Solana wallet
+    RotateAuthority { new_authority: Pubkey },
+    // Block (or unblock) the Solana authority from managing the identity
+    SetFrozen { frozen: bool },
+}
+
+impl EvmCommand {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            EvmCommand::RemoveLink { account_id } => {
+                out.push(0);
+                out.extend_from_slice(&(account_id.len() as u32).to_le_bytes());
+                out.extend_from_slice(account_id.as_bytes());
+            },
+            EvmCommand::RotateAuthority { new_authority } => {
+                out.push(1);
+                out.extend_from_slice(new_authority.as_ref());
+            },
+            EvmCommand::SetFrozen { frozen } => {
+                out.push(2);
+                out.push(*frozen as u8);
+            },
+        }
+    }
+}
+
+// A 65 byte EVM signature split into `r || s` and `v`
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct EvmSignature {
+    pub signature: [u8; 64],
+    pub recovery_id: u8, // `v`, either 0/1 or 27/28 as returned by EVM wallets
+}
+
+// Message the EVM manager signs to run `command` on the identity of `authority`.
+// `program_id` pins the command to one deployment, `nonce` to one use.
+pub fn evm_command_message(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    nonce: u64,
+    command: &EvmCommand,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(EVM_COMMAND_DOMAIN.len() + 32 + 32 + 8 + 64);
+    message.extend_from_slice(EVM_COMMAND_DOMAIN);
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(authority.as_ref());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    command.encode(&mut message);
+    message
+}
+
+// Digest an EVM wallet signs for `message` with `personal_sign`
+pub fn personal_sign_hash(message: &[u8]) -> [u8; 32] {
+    keccak::hashv(&[PERSONAL_SIGN_PREFIX, message.len().to_string().as_bytes(), message]).to_bytes()
+}
+
+// The EVM address whose key signed `message` with `personal_sign`
+pub fn recover_evm_signer(message: &[u8], signature: &EvmSignature) -> Result<[u8; 20]> {
+    let recovery_id = match signature.recovery_id {
+        0 | 1 => signature.recovery_id,
+        27 | 28 => signature.recovery_id - 27,
+        _ => return Err(error!(IdentityError::InvalidSignature)),
+    };
+    let public_key =
+        secp256k1_recover(&personal_sign_hash(message), recovery_id, &signature.signature)
+            .map_err(|_| error!(IdentityError::InvalidSignature))?;
+    let hash = keccak::hash(&public_key.to_bytes()).to_bytes();
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // Address of the secp256k1 private key 1
+    const KEY_ONE_ADDRESS: &str = "7e5f4552091a69125d5dfcb7b8c2659029395bdf";
+
+    fn set_frozen_message() -> Vec<u8> {
+        evm_command_message(
+            &Pubkey::new_from_array([1; 32]),
+            &Pubkey::new_from_array([2; 32]),
+            7,
+            &EvmCommand::SetFrozen { frozen: true },
+        )
+    }
+
+    // `personal_sign` of `set_frozen_message` with private key 1
+    fn set_frozen_signature(recovery_id: u8) -> EvmSignature {
+        let signature = hex("9df4827a8b73d8cfaf97aaf016b1c7d44a2a40764c314df1ffc18adc82c4bacf\
+             233d57626de08c18f8abc0d083b96757ab98a3c353742b24604761003d238798");
+        EvmSignature { signature: signature.try_into().unwrap(), recovery_id }
+    }
+
+    #[test]
+    fn personal_sign_hash_matches_eip191() {
+        assert_eq!(
+            personal_sign_hash(b"hello").to_vec(),
+            hex("50b2c43fd39106bafbba0da34fc430e1f91e3c96ea2acee2bc34119f92b37750")
+        );
+    }
+
+    #[test]
+    fn command_message_layout() {
+        let mut expected = EVM_COMMAND_DOMAIN.to_vec();
+        expected.extend_from_slice(&[1; 32]);
+        expected.extend_from_slice(&[2; 32]);
+        expected.extend_from_slice(&7u64.to_le_bytes());
+        expected.extend_from_slice(&[2, 1]);
+        assert_eq!(set_frozen_message(), expected);
+
+        let remove = evm_command_message(
+            &Pubkey::new_from_array([1; 32]),
+            &Pubkey::new_from_array([2; 32]),
+            7,
+            &EvmCommand::RemoveLink { account_id: "eip155:1:0xab".to_string() },
+        );
+        let mut tail = vec![0, 13, 0, 0, 0];
+        tail.extend_from_slice(b"eip155:1:0xab");
+        assert!(remove.ends_with(&tail));
+
+        let rotate = evm_command_message(
+            &Pubkey::new_from_array([1; 32]),
+            &Pubkey::new_from_array([2; 32]),
+            7,
+            &EvmCommand::RotateAuthority { new_authority: Pubkey::new_from_array([3; 32]) },
+        );
+        let mut tail = vec![1];
+        tail.extend_from_slice(&[3; 32]);
+        assert!(rotate.ends_with(&tail));
+    }
+
+    #[test]
+    fn recovers_signer_with_either_v() {
+        let message = set_frozen_message();
+        for recovery_id in [1, 28] {
+            let signer = recover_evm_signer(&message, &set_frozen_signature(recovery_id)).unwrap();
+            assert_eq!(signer.to_vec(), hex(KEY_ONE_ADDRESS));
+        }
+    }
+
+    #[test]
+    fn other_message_recovers_another_signer() {
+        let message = evm_command_message(
+            &Pubkey::new_from_array([1; 32]),
+            &Pubkey::new_from_array([2; 32]),
+            8,
+            &EvmCommand::SetFrozen { frozen: true },
+        );
+        let signer = recover_evm_signer(&message, &set_frozen_signature(1));
+        assert!(signer.map_or(true, |signer| signer.to_vec() != hex(KEY_ONE_ADDRESS)));
+    }
+
+    #[test]
+    fn rejects_bad_recovery_id() {
+        let message = set_frozen_message();
+        for recovery_id in [2, 26, 29, 255] {
+            assert!(recover_evm_signer(&message, &set_frozen_signature(recovery_id)).is_err());
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use crate::link::{LinkExpiry, LinkedAddress, PendingLink};
+
+// Seed for identity account PDAs: [IDENTITY_SEED, authority]
+pub const IDENTITY_SEED: &[u8] = b"identity";
+
+// Seed for DAO snapshot PDAs: [DAO_SNAPSHOT_SEED, dao name, block number (le)]
+pub const DAO_SNAPSHOT_SEED: &[u8] = b"dao_snapshot";
+
+// Seed for membership PDAs: [MEMBERSHIP_SEED, snapshot, evm address]
+pub const MEMBERSHIP_SEED: &[u8] = b"membership";
+
+// Seed for bulk link import PDAs: [LINK_IMPORT_SEED, import id (le)]
+pub const LINK_IMPORT_SEED: &[u8] = b"link_import";
+
+// Seed for import claim bitmap PDAs: [IMPORT_CLAIMS_SEED, link import, chunk (le)]
+pub const IMPORT_CLAIMS_SEED: &[u8] = b"import_claims";
+
+// Seed for pending cross-chain query PDAs: [PENDING_QUERY_SEED, guid of the query]
+pub const PENDING_QUERY_SEED: &[u8] = b"pending_query";
+
+// Seed for answers to inbound verification queries: [VERIFICATION_RESPONSE_SEED, guid of the query]
+pub const VERIFICATION_RESPONSE_SEED: &[u8] = b"verification_response";
+
+// Seed for chunked message buffers: [REASSEMBLY_SEED, src eid (be), message id]
+pub const REASSEMBLY_SEED: &[u8] = b"reassembly";
+
+// Seed for the fee sponsorship treasury PDA: [TREASURY_SEED]
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+// Seed for per-user sponsorship records: [SPONSORSHIP_SEED, user]
+pub const SPONSORSHIP_SEED: &[u8] = b"sponsorship";
+
+// Seed for guardian recovery PDAs: [RECOVERY_SEED, identity account]
+pub const RECOVERY_SEED: &[u8] = b"recovery";
+
+// Seed for delegation PDAs: [DELEGATION_SEED, identity account, delegate]
+pub const DELEGATION_SEED: &[u8] = b"delegation";
+
+// Seed for the records left by closed identities: [TOMBSTONE_SEED, authority]
+pub const TOMBSTONE_SEED: &[u8] = b"tombstone";
+
+// Allow up to 10 linked addresses per identity
+pub const MAX_LINKED_ADDRESSES: usize = 10;
+
+// Most link requests from EVM an identity holds until its authority reviews them
+pub const MAX_PENDING_LINKS: usize = 5;
+
+// Space taken by an identity account, discriminator included.
+// The layout is: authority (Pubkey), linked_addresses (Vec<LinkedAddress>), bump (u8),
+// primary (Option<u8>), evm_manager (Option<[u8; 20]>), evm_nonce (u64), frozen (bool),
+// link_expiries (Vec<LinkExpiry>), attestation_nonce (u64), pending_links (Vec<PendingLink>),
+// open_grants (u8)
+pub const IDENTITY_ACCOUNT_SIZE: usize = 8 +    // Discriminator
+    32 +                                        // authority: Pubkey
+    1 +                                         // bump: u8
+    4 +                                         // vec length
+    LinkedAddress::MAX_SIZE * MAX_LINKED_ADDRESSES + // addresses storage
+    2 +                                         // primary: Option<u8>
+    1 + 20 +                                    // evm_manager: Option<[u8; 20]>
+    8 +                                         // evm_nonce: u64
+    1 +                                         // frozen: bool
+    4 + LinkExpiry::SIZE * MAX_LINKED_ADDRESSES + // link_expiries: Vec<LinkExpiry>
+    8 +                                         // attestation_nonce: u64
+    4 + PendingLink::SIZE * MAX_PENDING_LINKS + // pending_links: Vec<PendingLink>
+    1;                                          // open_grants: u8
@@ -0,0 +1,32 @@
+// -----------------------------------------------------------------------------
+// Shared building blocks for the identity programs. Both `my_oapp` and the
+// legacy `identity-linker` program depend on this crate so that the wire
+// format, the address rules and the account sizing only exist in one place.
+// -----------------------------------------------------------------------------
+
+pub mod address;
+pub mod attestation;
+pub mod bech32;
+pub mod caip;
+pub mod chain_address;
+pub mod errors;
+pub mod evm_command;
+pub mod layout;
+pub mod link;
+pub mod merkle;
+pub mod message;
+pub mod options;
+pub mod query;
+
+pub use address::*;
+pub use attestation::*;
+pub use caip::*;
+pub use chain_address::*;
+pub use errors::*;
+pub use evm_command::*;
+pub use layout::*;
+pub use link::*;
+pub use merkle::*;
+pub use message::*;
+pub use options::*;
+pub use query::*;
@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+
+use crate::{caip::MAX_CHAIN_REFERENCE_LENGTH, chain_address::ChainAddress};
+
+// How much trust a link carries
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum LinkVerification {
+    // Added by the Solana authority, without proof of control of the address
+    SelfAsserted,
+    // Vouched for by a trusted attestor registered on the OApp Store
+    Attested { attestor_id: u32 },
+    // Was attested by an attestor that has since been revoked
+    Downgraded { attestor_id: u32 },
+    // Claimed from an admin-published bulk import of earlier off-chain links
+    Imported { import_id: u32 },
+}
+
+impl LinkVerification {
+    pub const SIZE: usize = 1 + 4;
+
+    // Orders verifications from the weakest to the strongest evidence
+    pub fn strength(&self) -> u8 {
+        match self {
+            LinkVerification::Downgraded { .. } => 0,
+            LinkVerification::SelfAsserted => 1,
+            LinkVerification::Imported { .. } => 2,
+            LinkVerification::Attested { .. } => 3,
+        }
+    }
+
+    // Whether control of the address was shown to someone other than the authority
+    pub fn is_verified(&self) -> bool {
+        matches!(self, LinkVerification::Attested { .. } | LinkVerification::Imported { .. })
+    }
+}
+
+// Expiry of one link, by its index in the identity's link set. Links without
+// an entry never expire.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct LinkExpiry {
+    pub index: u8,
+    pub expires_at: i64, // Unix timestamp from which the link no longer counts
+}
+
+impl LinkExpiry {
+    pub const SIZE: usize = 1 + 8;
+}
+
+// An EVM address that asked, through a LinkBatch message, to be linked to an
+// identity. It only becomes a link once the identity's authority accepts it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct PendingLink {
+    pub evm_address: [u8; 20],
+    pub primary: bool, // Also asked to become the primary address of the identity
+    pub received_at: i64, // Unix timestamp of the message that brought it
+}
+
+impl PendingLink {
+    pub const SIZE: usize = 20 + 1 + 8;
+}
+
+// One entry of an identity's link set: a validated address, optionally scoped
+// to a single chain (the CAIP-2 reference, e.g. "1" for Ethereum mainnet).
+// `chain_reference` is None for chain agnostic links.
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct LinkedAddress {
+    pub address: ChainAddress,
+    pub chain_reference: Option<String>,
+    pub verification: LinkVerification,
+}
+
+impl LinkedAddress {
+    pub const MAX_SIZE: usize = ChainAddress::MAX_SIZE + // address
+        1 + 4 + MAX_CHAIN_REFERENCE_LENGTH + // chain_reference
+        LinkVerification::SIZE; // verification
+
+    // New links start out self asserted
+    pub fn new(address: ChainAddress, chain_reference: Option<String>) -> Self {
+        LinkedAddress { address, chain_reference, verification: LinkVerification::SelfAsserted }
+    }
+
+    // A chain agnostic link covers the address on every chain of its namespace,
+    // and a chain agnostic query is answered by a link on any chain.
+    pub fn covers(&self, query: &LinkedAddress) -> bool {
+        if self.address != query.address {
+            return false;
+        }
+        match (&self.chain_reference, &query.chain_reference) {
+            (None, _) | (_, None) => true,
+            (Some(linked), Some(queried)) => linked == queried,
+        }
+    }
+
+    // True when adding `other` would not extend what this link already covers
+    pub fn subsumes(&self, other: &LinkedAddress) -> bool {
+        self.address == other.address
+            && (self.chain_reference.is_none() || self.chain_reference == other.chain_reference)
+    }
+}
@@ -0,0 +1,90 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::keccak;
+
+// -----------------------------------------------------------------------------
+// Merkle proofs for DAO membership snapshots and bulk link imports. The tree
+// format is the one of OpenZeppelin's StandardMerkleTree, so roots can be
+// built with the usual EVM tooling:
+//   leaf = keccak256(keccak256(abi.encode(...values)))
+//   node = keccak256(min(a, b) ++ max(a, b))
+// -----------------------------------------------------------------------------
+
+// Leaf of a DAO snapshot holder and its weight, for `["address", "uint256"]` values
+pub fn membership_leaf(evm_address: &[u8; 20], weight: u64) -> [u8; 32] {
+    // abi.encode pads both values to 32 bytes, big endian
+    let mut encoded = [0u8; 64];
+    encoded[12..32].copy_from_slice(evm_address);
+    encoded[56..64].copy_from_slice(&weight.to_be_bytes());
+    let inner = keccak::hash(&encoded).to_bytes();
+    keccak::hash(&inner).to_bytes()
+}
+
+// Leaf of a bulk link import, for `["uint256", "bytes32", "address"]` values.
+// `index` is the position of the pair in the import and selects its claim bit.
+pub fn import_leaf(index: u64, solana_address: &Pubkey, evm_address: &[u8; 20]) -> [u8; 32] {
+    let mut encoded = [0u8; 96];
+    encoded[24..32].copy_from_slice(&index.to_be_bytes());
+    encoded[32..64].copy_from_slice(solana_address.as_ref());
+    encoded[76..96].copy_from_slice(evm_address);
+    let inner = keccak::hash(&encoded).to_bytes();
+    keccak::hash(&inner).to_bytes()
+}
+
+// Recomputes the root from `leaf` and its sibling hashes and compares it with `root`
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).to_bytes()
+        } else {
+            keccak::hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex32(s: &str) -> [u8; 32] {
+        let bytes: Vec<u8> = (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect();
+        bytes.try_into().unwrap()
+    }
+
+    // Example tree of the OpenZeppelin merkle-tree README:
+    // StandardMerkleTree.of([[0x1111…, "5000000000000000000"], [0x2222…, "2500000000000000000"]],
+    // ["address", "uint256"])
+    #[test]
+    fn membership_leaves_match_standard_merkle_tree() {
+        let root = hex32("d4dee0beab2d53f2cc83e567171bd2820e49898130a22622b10ead383e90bd77");
+        let first = membership_leaf(&[0x11; 20], 5_000_000_000_000_000_000);
+        let second = membership_leaf(&[0x22; 20], 2_500_000_000_000_000_000);
+        assert!(verify_merkle_proof(&[second], &root, first));
+        assert!(verify_merkle_proof(&[first], &root, second));
+        assert!(!verify_merkle_proof(&[second], &root, membership_leaf(&[0x11; 20], 1)));
+    }
+
+    // Leaves of a `["uint256", "bytes32", "address"]` tree, hashed with an
+    // independent keccak implementation
+    #[test]
+    fn import_leaves_match_standard_merkle_tree() {
+        let first = import_leaf(7, &Pubkey::new_from_array([0x01; 32]), &[0x22; 20]);
+        let second = import_leaf(8, &Pubkey::new_from_array([0x03; 32]), &[0x44; 20]);
+        assert_eq!(
+            first,
+            hex32("ac600d42b6bd8837e60f2dbec9922686dfaf512ea6bde6024226037dfb11454d")
+        );
+        assert_eq!(
+            second,
+            hex32("abc68df256145f76b0c4c5f1e06cc07c67c888dff08394216513afa219bcdd36")
+        );
+        let root = hex32("4755425395ab61175a86ba668066c8f6fd525dc7ece0b3bc76b2634276affa97");
+        assert!(verify_merkle_proof(&[second], &root, first));
+        // The index is part of the leaf, so a pair can't be claimed under another bit
+        let moved = import_leaf(9, &Pubkey::new_from_array([0x01; 32]), &[0x22; 20]);
+        assert!(!verify_merkle_proof(&[second], &root, moved));
+    }
+}
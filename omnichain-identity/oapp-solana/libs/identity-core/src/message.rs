@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use std::str;
+
+use crate::{
+    caip::{format_account_id, parse_account_id},
+    chain_address::{AddressNamespace, ChainAddress},
+    errors::IdentityError,
+    link::LinkedAddress,
+};
+
+// -----------------------------------------------------------------------------
+// Identity link messages sent by `OmnichainIdentityLinker` on EVM. The payload
+// is a plain UTF-8 CSV string: "address,solanaAddress,timestamp".
+// The address is either a bare EVM address, which is what the deployed EVM
+// linker sends and is linked chain agnostic, or a CAIP-10 account id carrying
+// its own namespace and chain.
+// -----------------------------------------------------------------------------
+
+// Identity message structure
+#[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct IdentityMessage {
+    pub linked_address: LinkedAddress, // Address being linked, with its namespace and chain
+    pub solana_address: String,        // Solana wallet address
+    pub timestamp: i64,                // Timestamp for the link
+}
+
+impl IdentityMessage {
+    // Parse the Solana side of the link into a pubkey
+    pub fn solana_pubkey(&self) -> Result<Pubkey> {
+        self.solana_address.parse::<Pubkey>().map_err(|_| error!(IdentityError::InvalidAddress))
+    }
+}
+
+// Encode an identity message in the same CSV layout the EVM linker produces.
+// Chain agnostic EVM addresses are written bare, anything else as CAIP-10.
+pub fn encode_identity_message(message: &IdentityMessage) -> Vec<u8> {
+    let link = &message.linked_address;
+    let address = if link.address.namespace() == AddressNamespace::Evm
+        && link.chain_reference.is_none()
+    {
+        link.address.to_string()
+    } else {
+        format_account_id(link)
+    };
+    format!("{},{},{}", address, message.solana_address, message.timestamp).into_bytes()
+}
+
+pub fn decode_identity_message(message: &[u8]) -> Result<IdentityMessage> {
+    let message_str = match str::from_utf8(message) {
+        Ok(s) => s,
+        Err(_) => return Err(error!(IdentityError::InvalidMessageFormat)),
+    };
+
+    // Split by comma for simple parsing (CSV-like format)
+    let parts: Vec<&str> = message_str.split(',').collect();
+    if parts.len() < 3 {
+        return Err(error!(IdentityError::InvalidMessageFormat));
+    }
+
+    // Extract parts, validating the linked address with its namespace's rules
+    let address = parts[0].trim();
+    let linked_address = if address.contains(':') {
+        parse_account_id(address)?
+    } else {
+        LinkedAddress::new(ChainAddress::parse(AddressNamespace::Evm, address)?, None)
+    };
+    let solana_address = parts[1].trim().to_string();
+
+    // Parse timestamp
+    let timestamp = match parts[2].trim().parse::<i64>() {
+        Ok(t) => t,
+        Err(_) => return Err(error!(IdentityError::InvalidMessageFormat)),
+    };
+
+    Ok(IdentityMessage { linked_address, solana_address, timestamp })
+}
@@ -0,0 +1,438 @@
+use anchor_lang::prelude::*;
+use std::fmt;
+
+use crate::errors::IdentityError;
+
+// -----------------------------------------------------------------------------
+// LayerZero V2 type 3 execution options, byte for byte what `OptionsBuilder.sol`
+// and `Options` from lz-v2-utilities produce:
+//
+//   type (u16 = 3) | { worker id (u8) | size (u16) | option type (u8) | data }*
+//
+// `size` counts the option type byte and its data. Executor option data:
+//   lzReceive         gas (u128) [| value (u128)]
+//   nativeDrop        amount (u128) | receiver (bytes32)
+//   lzCompose         index (u16) | gas (u128) [| value (u128)]
+//   orderedExecution  -
+// A zero value is left out, as the EVM builder does. Integers are big-endian.
+// -----------------------------------------------------------------------------
+
+pub const OPTIONS_TYPE_3: u16 = 3;
+
+pub const EXECUTOR_WORKER_ID: u8 = 1;
+pub const DVN_WORKER_ID: u8 = 2;
+
+pub const OPTION_TYPE_LZRECEIVE: u8 = 1;
+pub const OPTION_TYPE_NATIVE_DROP: u8 = 2;
+pub const OPTION_TYPE_LZCOMPOSE: u8 = 3;
+pub const OPTION_TYPE_ORDERED_EXECUTION: u8 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutorOption {
+    LzReceive { gas: u128, value: u128 },
+    NativeDrop { amount: u128, receiver: [u8; 32] },
+    LzCompose { index: u16, gas: u128, value: u128 },
+    OrderedExecution,
+}
+
+impl ExecutorOption {
+    pub fn option_type(&self) -> u8 {
+        match self {
+            ExecutorOption::LzReceive { .. } => OPTION_TYPE_LZRECEIVE,
+            ExecutorOption::NativeDrop { .. } => OPTION_TYPE_NATIVE_DROP,
+            ExecutorOption::LzCompose { .. } => OPTION_TYPE_LZCOMPOSE,
+            ExecutorOption::OrderedExecution => OPTION_TYPE_ORDERED_EXECUTION,
+        }
+    }
+
+    fn data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        match self {
+            ExecutorOption::LzReceive { gas, value } => {
+                data.extend_from_slice(&gas.to_be_bytes());
+                if *value > 0 {
+                    data.extend_from_slice(&value.to_be_bytes());
+                }
+            },
+            ExecutorOption::NativeDrop { amount, receiver } => {
+                data.extend_from_slice(&amount.to_be_bytes());
+                data.extend_from_slice(receiver);
+            },
+            ExecutorOption::LzCompose { index, gas, value } => {
+                data.extend_from_slice(&index.to_be_bytes());
+                data.extend_from_slice(&gas.to_be_bytes());
+                if *value > 0 {
+                    data.extend_from_slice(&value.to_be_bytes());
+                }
+            },
+            ExecutorOption::OrderedExecution => {},
+        }
+        data
+    }
+
+    fn decode(option_type: u8, data: &[u8]) -> Result<Self> {
+        let option = match (option_type, data.len()) {
+            (OPTION_TYPE_LZRECEIVE, 16) | (OPTION_TYPE_LZRECEIVE, 32) => {
+                ExecutorOption::LzReceive {
+                    gas: read_u128(data, 0),
+                    value: read_u128_or_zero(data, 16),
+                }
+            },
+            (OPTION_TYPE_NATIVE_DROP, 48) => {
+                let mut receiver = [0u8; 32];
+                receiver.copy_from_slice(&data[16..48]);
+                ExecutorOption::NativeDrop { amount: read_u128(data, 0), receiver }
+            },
+            (OPTION_TYPE_LZCOMPOSE, 18) | (OPTION_TYPE_LZCOMPOSE, 34) => {
+                ExecutorOption::LzCompose {
+                    index: u16::from_be_bytes([data[0], data[1]]),
+                    gas: read_u128(data, 2),
+                    value: read_u128_or_zero(data, 18),
+                }
+            },
+            (OPTION_TYPE_ORDERED_EXECUTION, 0) => ExecutorOption::OrderedExecution,
+            _ => {
+                msg!("Invalid executor option {} of {} bytes", option_type, data.len());
+                return Err(error!(IdentityError::InvalidOptions));
+            },
+        };
+        Ok(option)
+    }
+}
+
+impl fmt::Display for ExecutorOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutorOption::LzReceive { gas, value } => {
+                write!(f, "lzReceive gas={} value={}", gas, value)
+            },
+            ExecutorOption::NativeDrop { amount, receiver } => {
+                write!(f, "nativeDrop amount={} receiver=0x", amount)?;
+                receiver.iter().try_for_each(|b| write!(f, "{:02x}", b))
+            },
+            ExecutorOption::LzCompose { index, gas, value } => {
+                write!(f, "lzCompose index={} gas={} value={}", index, gas, value)
+            },
+            ExecutorOption::OrderedExecution => write!(f, "orderedExecution"),
+        }
+    }
+}
+
+// A single option of an options blob. Options for other workers, such as DVNs,
+// are kept as raw bytes so that decoding and re-encoding never loses anything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkerOption {
+    Executor(ExecutorOption),
+    Other { worker_id: u8, option_type: u8, data: Vec<u8> },
+}
+
+impl fmt::Display for WorkerOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkerOption::Executor(option) => write!(f, "executor {}", option),
+            WorkerOption::Other { worker_id, option_type, data } => {
+                write!(f, "worker {} option {} data=0x", worker_id, option_type)?;
+                data.iter().try_for_each(|b| write!(f, "{:02x}", b))
+            },
+        }
+    }
+}
+
+// Decoded type 3 options. Empty bytes decode to no options, which is how
+// LayerZero represents "nothing enforced" and "no extra options".
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionOptions {
+    pub options: Vec<WorkerOption>,
+}
+
+impl ExecutionOptions {
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.is_empty() {
+            return Ok(Self::default());
+        }
+        if bytes.len() < 2 || u16::from_be_bytes([bytes[0], bytes[1]]) != OPTIONS_TYPE_3 {
+            msg!("Options are not type 3");
+            return Err(error!(IdentityError::InvalidOptions));
+        }
+
+        let mut options = Vec::new();
+        let mut cursor = 2;
+        while cursor < bytes.len() {
+            // Worker id, size and option type
+            if bytes.len() < cursor + 4 {
+                return Err(error!(IdentityError::InvalidOptions));
+            }
+            let worker_id = bytes[cursor];
+            let size = u16::from_be_bytes([bytes[cursor + 1], bytes[cursor + 2]]) as usize;
+            let option_type = bytes[cursor + 3];
+            if size == 0 || bytes.len() < cursor + 3 + size {
+                return Err(error!(IdentityError::InvalidOptions));
+            }
+            let data = &bytes[cursor + 4..cursor + 3 + size];
+
+            options.push(match worker_id {
+                EXECUTOR_WORKER_ID => {
+                    WorkerOption::Executor(ExecutorOption::decode(option_type, data)?)
+                },
+                _ => WorkerOption::Other { worker_id, option_type, data: data.to_vec() },
+            });
+            cursor += 3 + size;
+        }
+        Ok(Self { options })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = OPTIONS_TYPE_3.to_be_bytes().to_vec();
+        for option in &self.options {
+            let (worker_id, option_type, data) = match option {
+                WorkerOption::Executor(option) => {
+                    (EXECUTOR_WORKER_ID, option.option_type(), option.data())
+                },
+                WorkerOption::Other { worker_id, option_type, data } => {
+                    (*worker_id, *option_type, data.clone())
+                },
+            };
+            bytes.push(worker_id);
+            bytes.extend_from_slice(&((data.len() + 1) as u16).to_be_bytes());
+            bytes.push(option_type);
+            bytes.extend_from_slice(&data);
+        }
+        bytes
+    }
+
+    pub fn executor_options(&self) -> impl Iterator<Item = &ExecutorOption> {
+        self.options.iter().filter_map(|option| match option {
+            WorkerOption::Executor(option) => Some(option),
+            WorkerOption::Other { .. } => None,
+        })
+    }
+
+    // Total lzReceive gas and value. The executor adds repeated lzReceive options
+    // up, which is what happens when extra options are appended to enforced ones.
+    pub fn lz_receive(&self) -> (u128, u128) {
+        self.executor_options().fold((0, 0), |(gas, value), option| match option {
+            ExecutorOption::LzReceive { gas: g, value: v } => {
+                (gas.saturating_add(*g), value.saturating_add(*v))
+            },
+            _ => (gas, value),
+        })
+    }
+
+    pub fn lz_receive_gas(&self) -> u128 {
+        self.lz_receive().0
+    }
+
+    // Total gas and value for the compose call at `index`
+    pub fn lz_compose(&self, index: u16) -> (u128, u128) {
+        self.executor_options().fold((0, 0), |(gas, value), option| match option {
+            ExecutorOption::LzCompose { index: i, gas: g, value: v } if *i == index => {
+                (gas.saturating_add(*g), value.saturating_add(*v))
+            },
+            _ => (gas, value),
+        })
+    }
+
+    pub fn ordered_execution(&self) -> bool {
+        self.executor_options()
+            .any(|option| *option == ExecutorOption::OrderedExecution)
+    }
+
+    // Fails when the lzReceive gas is below what the destination needs
+    pub fn assert_min_lz_receive_gas(&self, min_gas: u128) -> Result<()> {
+        let gas = self.lz_receive_gas();
+        if gas < min_gas {
+            msg!("lzReceive gas {} is below the minimum of {}", gas, min_gas);
+            return Err(error!(IdentityError::InsufficientLzReceiveGas));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ExecutionOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.options.is_empty() {
+            return write!(f, "no options");
+        }
+        for (i, option) in self.options.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", option)?;
+        }
+        Ok(())
+    }
+}
+
+// Builds type 3 options, mirroring `OptionsBuilder.sol`:
+//   OptionsBuilder::new().lz_receive(200_000, 0).ordered_execution().build()
+#[derive(Debug, Clone, Default)]
+pub struct OptionsBuilder {
+    options: ExecutionOptions,
+}
+
+impl OptionsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn lz_receive(self, gas: u128, value: u128) -> Self {
+        self.executor(ExecutorOption::LzReceive { gas, value })
+    }
+
+    pub fn native_drop(self, amount: u128, receiver: [u8; 32]) -> Self {
+        self.executor(ExecutorOption::NativeDrop { amount, receiver })
+    }
+
+    pub fn lz_compose(self, index: u16, gas: u128, value: u128) -> Self {
+        self.executor(ExecutorOption::LzCompose { index, gas, value })
+    }
+
+    pub fn ordered_execution(self) -> Self {
+        self.executor(ExecutorOption::OrderedExecution)
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        self.options.encode()
+    }
+
+    fn executor(mut self, option: ExecutorOption) -> Self {
+        self.options.options.push(WorkerOption::Executor(option));
+        self
+    }
+}
+
+fn read_u128(data: &[u8], offset: usize) -> u128 {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&data[offset..offset + 16]);
+    u128::from_be_bytes(bytes)
+}
+
+fn read_u128_or_zero(data: &[u8], offset: usize) -> u128 {
+    if data.len() > offset {
+        read_u128(data, offset)
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // OptionsBuilder.newOptions().addExecutorLzReceiveOption(200_000, 0)
+    const LZ_RECEIVE_200K: &str = "00030100110100000000000000000000000000030d40";
+
+    #[test]
+    fn builder_matches_evm_options_builder() {
+        assert_eq!(OptionsBuilder::new().lz_receive(200_000, 0).build(), hex(LZ_RECEIVE_200K));
+        // addExecutorOrderedExecutionOption()
+        assert_eq!(
+            OptionsBuilder::new().lz_receive(200_000, 0).ordered_execution().build(),
+            hex(&format!("{}{}", LZ_RECEIVE_200K, "01000104"))
+        );
+        assert_eq!(OptionsBuilder::new().build(), hex("0003"));
+    }
+
+    #[test]
+    fn size_counts_option_type_and_data() {
+        // A zero value is left out: 1 + 16 bytes
+        let bytes = OptionsBuilder::new().lz_receive(1, 0).build();
+        assert_eq!(u16::from_be_bytes([bytes[3], bytes[4]]), 17);
+        assert_eq!(bytes.len(), 2 + 3 + 17);
+        // A value adds another 16
+        let bytes = OptionsBuilder::new().lz_receive(1, 2).build();
+        assert_eq!(u16::from_be_bytes([bytes[3], bytes[4]]), 33);
+        assert_eq!(bytes.len(), 2 + 3 + 33);
+        // lzCompose carries the index, ordered execution carries nothing
+        let bytes = OptionsBuilder::new().lz_compose(0, 1, 0).build();
+        assert_eq!(u16::from_be_bytes([bytes[3], bytes[4]]), 19);
+        let bytes = OptionsBuilder::new().ordered_execution().build();
+        assert_eq!(u16::from_be_bytes([bytes[3], bytes[4]]), 1);
+    }
+
+    #[test]
+    fn round_trips_every_executor_option() {
+        let bytes = OptionsBuilder::new()
+            .lz_receive(200_000, 0)
+            .lz_receive(50_000, 1_000)
+            .native_drop(7, [0xab; 32])
+            .lz_compose(1, 90_000, 0)
+            .lz_compose(2, 10_000, 5)
+            .ordered_execution()
+            .build();
+        let options = ExecutionOptions::decode(&bytes).unwrap();
+        assert_eq!(options.options.len(), 6);
+        assert_eq!(options.encode(), bytes);
+        assert_eq!(
+            options.options[2],
+            WorkerOption::Executor(ExecutorOption::NativeDrop { amount: 7, receiver: [0xab; 32] })
+        );
+        assert!(options.ordered_execution());
+    }
+
+    #[test]
+    fn passes_dvn_options_through() {
+        // addDVNPreCrimeOption(0) after an executor option
+        let bytes = hex(&format!("{}{}", LZ_RECEIVE_200K, "0200020100"));
+        let options = ExecutionOptions::decode(&bytes).unwrap();
+        assert_eq!(
+            options.options[1],
+            WorkerOption::Other { worker_id: DVN_WORKER_ID, option_type: 1, data: vec![0] }
+        );
+        assert_eq!(options.executor_options().count(), 1);
+        assert_eq!(options.encode(), bytes);
+    }
+
+    #[test]
+    fn sums_repeated_lz_receive_and_compose() {
+        // Extra options appended to enforced ones
+        let mut bytes = OptionsBuilder::new().lz_receive(200_000, 0).build();
+        bytes.extend_from_slice(&OptionsBuilder::new().lz_receive(50_000, 3).build()[2..]);
+        bytes.extend_from_slice(&OptionsBuilder::new().lz_compose(0, 10, 1).build()[2..]);
+        bytes.extend_from_slice(&OptionsBuilder::new().lz_compose(1, 20, 0).build()[2..]);
+        bytes.extend_from_slice(&OptionsBuilder::new().lz_compose(0, 30, 2).build()[2..]);
+        let options = ExecutionOptions::decode(&bytes).unwrap();
+        assert_eq!(options.lz_receive(), (250_000, 3));
+        assert_eq!(options.lz_compose(0), (40, 3));
+        assert_eq!(options.lz_compose(1), (20, 0));
+        assert_eq!(options.lz_compose(2), (0, 0));
+        assert!(!options.ordered_execution());
+    }
+
+    #[test]
+    fn asserts_minimum_lz_receive_gas() {
+        let options = ExecutionOptions::decode(&hex(LZ_RECEIVE_200K)).unwrap();
+        assert!(options.assert_min_lz_receive_gas(200_000).is_ok());
+        assert!(options.assert_min_lz_receive_gas(200_001).is_err());
+        // No options carry no gas
+        let empty = ExecutionOptions::decode(&[]).unwrap();
+        assert_eq!(empty, ExecutionOptions::default());
+        assert!(empty.assert_min_lz_receive_gas(0).is_ok());
+        assert!(empty.assert_min_lz_receive_gas(1).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_options() {
+        // Not type 3
+        assert!(ExecutionOptions::decode(&hex("0001")).is_err());
+        assert!(ExecutionOptions::decode(&hex("00")).is_err());
+        // Truncated header and data
+        assert!(ExecutionOptions::decode(&hex("0003010011")).is_err());
+        assert!(ExecutionOptions::decode(&hex(&LZ_RECEIVE_200K[..40])).is_err());
+        // Zero size
+        assert!(ExecutionOptions::decode(&hex("000301000001")).is_err());
+        // lzReceive with 15 bytes of gas
+        assert!(
+            ExecutionOptions::decode(&hex("000301001001000000000000000000000000030d40")).is_err()
+        );
+        // Unknown executor option
+        assert!(ExecutionOptions::decode(&hex("000301000109")).is_err());
+    }
+}
@@ -0,0 +1,641 @@
+use anchor_lang::prelude::*;
+use std::str;
+
+use crate::{errors::IdentityError, link::LinkVerification};
+
+// -----------------------------------------------------------------------------
+// Typed binary messages exchanged between the Solana OApp and its EVM peers.
+// The first byte is the message type. Types start at 1 so a typed message can't
+// be mistaken for the legacy payloads: the string codec starts with a zero
+// byte and identity link CSV starts with a printable character.
+// Integers are big endian, like `abi.encodePacked` produces on EVM.
+// -----------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MessageType {
+    // Solana -> EVM: which Solana addresses has this EVM account linked?
+    LinksQuery = 1,
+    // EVM -> Solana: answer to a LinksQuery
+    LinksResponse = 2,
+    // EVM -> Solana: is this EVM account linked to this Solana wallet?
+    VerifyQuery = 3,
+    // Solana -> EVM: answer to a VerifyQuery
+    VerifyResponse = 4,
+    // EVM -> Solana: several identity links in one message
+    LinkBatch = 5,
+    // Either way: one part of a message too large to be sent at once
+    Chunk = 6,
+    // Solana -> EVM: an identity moved to a new Solana wallet
+    AuthorityRotation = 7,
+}
+
+impl MessageType {
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(MessageType::LinksQuery),
+            2 => Some(MessageType::LinksResponse),
+            3 => Some(MessageType::VerifyQuery),
+            4 => Some(MessageType::VerifyResponse),
+            5 => Some(MessageType::LinkBatch),
+            6 => Some(MessageType::Chunk),
+            7 => Some(MessageType::AuthorityRotation),
+            _ => None,
+        }
+    }
+}
+
+// The type of a typed message, or None for legacy payloads
+pub fn message_type(message: &[u8]) -> Option<MessageType> {
+    message.first().copied().and_then(MessageType::from_tag)
+}
+
+// LinksQuery layout:
+// type (1) | evm_address (20) | compose_to (32)
+// `compose_to` is echoed back in the response so the Solana side can notify a
+// program through compose without reading any account first. All zeros means
+// no notification.
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct LinksQuery {
+    pub evm_address: [u8; 20],
+    pub compose_to: Pubkey,
+}
+
+impl LinksQuery {
+    pub const SIZE: usize = 1 + 20 + 32;
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::SIZE);
+        message.push(MessageType::LinksQuery as u8);
+        message.extend_from_slice(&self.evm_address);
+        message.extend_from_slice(self.compose_to.as_ref());
+        message
+    }
+}
+
+// LinksResponse layout:
+// type (1) | request_guid (32) | compose_to (32) | evm_address (20) | count (2)
+// | count x [ len (1) | base58 Solana address (len) ]
+// The EVM linker keeps linked Solana addresses as the strings users submitted,
+// so they are returned as is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinksResponse {
+    pub request_guid: [u8; 32], // guid of the LinksQuery being answered
+    pub compose_to: Pubkey,
+    pub evm_address: [u8; 20],
+    pub solana_addresses: Vec<String>,
+}
+
+impl LinksResponse {
+    const HEADER_SIZE: usize = 1 + 32 + 32 + 20 + 2;
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::HEADER_SIZE);
+        message.push(MessageType::LinksResponse as u8);
+        message.extend_from_slice(&self.request_guid);
+        message.extend_from_slice(self.compose_to.as_ref());
+        message.extend_from_slice(&self.evm_address);
+        message.extend_from_slice(&(self.solana_addresses.len() as u16).to_be_bytes());
+        for address in self.solana_addresses.iter() {
+            message.push(address.len() as u8);
+            message.extend_from_slice(address.as_bytes());
+        }
+        message
+    }
+
+    pub fn decode(message: &[u8]) -> Result<Self> {
+        if message.len() < Self::HEADER_SIZE
+            || message_type(message) != Some(MessageType::LinksResponse)
+        {
+            return Err(error!(IdentityError::InvalidMessageFormat));
+        }
+        let mut request_guid = [0u8; 32];
+        request_guid.copy_from_slice(&message[1..33]);
+        let compose_to = Pubkey::try_from(&message[33..65]).unwrap();
+        let mut evm_address = [0u8; 20];
+        evm_address.copy_from_slice(&message[65..85]);
+        let count = u16::from_be_bytes([message[85], message[86]]) as usize;
+
+        let mut solana_addresses = Vec::with_capacity(count);
+        let mut offset = Self::HEADER_SIZE;
+        for _ in 0..count {
+            let len =
+                *message.get(offset).ok_or_else(|| error!(IdentityError::InvalidMessageFormat))?
+                    as usize;
+            let address = message
+                .get(offset + 1..offset + 1 + len)
+                .and_then(|bytes| str::from_utf8(bytes).ok())
+                .ok_or_else(|| error!(IdentityError::InvalidMessageFormat))?;
+            solana_addresses.push(address.to_string());
+            offset += 1 + len;
+        }
+        if offset != message.len() {
+            return Err(error!(IdentityError::InvalidMessageFormat));
+        }
+
+        Ok(LinksResponse { request_guid, compose_to, evm_address, solana_addresses })
+    }
+}
+
+// VerifyQuery layout:
+// type (1) | evm_address (20) | solana_address (32) | chain_id (8) | response_fee (8)
+// `chain_id` is the EIP-155 chain the link must cover, 0 for any chain.
+// `response_fee` is the native fee in lamports for sending the response back,
+// delivered with the query through the executor's lzReceive value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyQuery {
+    pub evm_address: [u8; 20],
+    pub solana_address: Pubkey,
+    pub chain_id: u64,
+    pub response_fee: u64,
+}
+
+impl VerifyQuery {
+    pub const SIZE: usize = 1 + 20 + 32 + 8 + 8;
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::SIZE);
+        message.push(MessageType::VerifyQuery as u8);
+        message.extend_from_slice(&self.evm_address);
+        message.extend_from_slice(self.solana_address.as_ref());
+        message.extend_from_slice(&self.chain_id.to_be_bytes());
+        message.extend_from_slice(&self.response_fee.to_be_bytes());
+        message
+    }
+
+    pub fn decode(message: &[u8]) -> Result<Self> {
+        if message.len() != Self::SIZE || message_type(message) != Some(MessageType::VerifyQuery) {
+            return Err(error!(IdentityError::InvalidMessageFormat));
+        }
+        let mut evm_address = [0u8; 20];
+        evm_address.copy_from_slice(&message[1..21]);
+        let solana_address = Pubkey::try_from(&message[21..53]).unwrap();
+        let chain_id = u64::from_be_bytes(message[53..61].try_into().unwrap());
+        let response_fee = u64::from_be_bytes(message[61..69].try_into().unwrap());
+        Ok(VerifyQuery { evm_address, solana_address, chain_id, response_fee })
+    }
+}
+
+// How a queried link is verified, as sent to EVM: the level and, for the
+// attested, downgraded and imported levels, the attestor or import id.
+pub const LEVEL_NOT_LINKED: u8 = 0;
+pub const LEVEL_SELF_ASSERTED: u8 = 1;
+pub const LEVEL_ATTESTED: u8 = 2;
+pub const LEVEL_DOWNGRADED: u8 = 3;
+pub const LEVEL_IMPORTED: u8 = 4;
+
+pub fn verification_level(verification: Option<LinkVerification>) -> (u8, u32) {
+    match verification {
+        None => (LEVEL_NOT_LINKED, 0),
+        Some(LinkVerification::SelfAsserted) => (LEVEL_SELF_ASSERTED, 0),
+        Some(LinkVerification::Attested { attestor_id }) => (LEVEL_ATTESTED, attestor_id),
+        Some(LinkVerification::Downgraded { attestor_id }) => (LEVEL_DOWNGRADED, attestor_id),
+        Some(LinkVerification::Imported { import_id }) => (LEVEL_IMPORTED, import_id),
+    }
+}
+
+// VerifyResponse layout:
+// type (1) | request_guid (32) | evm_address (20) | solana_address (32) | level (1) | level_id (4)
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct VerifyResponse {
+    pub request_guid: [u8; 32], // guid of the VerifyQuery being answered
+    pub evm_address: [u8; 20],
+    pub solana_address: Pubkey,
+    pub verification: Option<LinkVerification>, // None when not linked
+}
+
+impl VerifyResponse {
+    pub const SIZE: usize = 1 + 32 + 20 + 32 + 1 + 4;
+
+    pub fn encode(&self) -> Vec<u8> {
+        let (level, level_id) = verification_level(self.verification);
+        let mut message = Vec::with_capacity(Self::SIZE);
+        message.push(MessageType::VerifyResponse as u8);
+        message.extend_from_slice(&self.request_guid);
+        message.extend_from_slice(&self.evm_address);
+        message.extend_from_slice(self.solana_address.as_ref());
+        message.push(level);
+        message.extend_from_slice(&level_id.to_be_bytes());
+        message
+    }
+}
+
+// Most links a LinkBatch can carry, so that the identity accounts of every
+// entry fit in the `lz_receive` transaction
+pub const MAX_BATCH_LINKS: usize = 5;
+
+// LinkBatch layout:
+// type (1) | timestamp (8) | count (1) | count x [ evm_address (20) | len (1) | base58 Solana address (len) ]
+//   [ | primary mask (1) ]
+// Solana addresses are the strings users submitted on EVM, so an entry can
+// carry an invalid one. It is rejected on its own when the batch is applied.
+// Bit `i` of the optional primary mask asks for entry `i` to become the
+// primary address of its identity; batches without one request nothing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkBatch {
+    pub timestamp: i64,
+    pub entries: Vec<LinkBatchEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkBatchEntry {
+    pub evm_address: [u8; 20],
+    pub solana_address: String,
+    pub primary: bool, // Make the EVM address the primary address of the identity
+}
+
+impl LinkBatchEntry {
+    pub fn solana_pubkey(&self) -> Option<Pubkey> {
+        self.solana_address.parse::<Pubkey>().ok()
+    }
+}
+
+impl LinkBatch {
+    const HEADER_SIZE: usize = 1 + 8 + 1;
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::HEADER_SIZE);
+        message.push(MessageType::LinkBatch as u8);
+        message.extend_from_slice(&self.timestamp.to_be_bytes());
+        message.push(self.entries.len() as u8);
+        for entry in self.entries.iter() {
+            message.extend_from_slice(&entry.evm_address);
+            message.push(entry.solana_address.len() as u8);
+            message.extend_from_slice(entry.solana_address.as_bytes());
+        }
+        let primary_mask = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.primary)
+            .fold(0u8, |mask, (index, _)| mask | 1 << index);
+        if primary_mask != 0 {
+            message.push(primary_mask);
+        }
+        message
+    }
+
+    pub fn decode(message: &[u8]) -> Result<Self> {
+        if message.len() < Self::HEADER_SIZE
+            || message_type(message) != Some(MessageType::LinkBatch)
+        {
+            return Err(error!(IdentityError::InvalidMessageFormat));
+        }
+        let timestamp = i64::from_be_bytes(message[1..9].try_into().unwrap());
+        let count = message[9] as usize;
+        if count > MAX_BATCH_LINKS {
+            return Err(error!(IdentityError::InvalidMessageFormat));
+        }
+
+        let mut entries = Vec::with_capacity(count);
+        let mut offset = Self::HEADER_SIZE;
+        for _ in 0..count {
+            let evm_address: [u8; 20] = message
+                .get(offset..offset + 20)
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| error!(IdentityError::InvalidMessageFormat))?;
+            let len = *message
+                .get(offset + 20)
+                .ok_or_else(|| error!(IdentityError::InvalidMessageFormat))?
+                as usize;
+            let solana_address = message
+                .get(offset + 21..offset + 21 + len)
+                .and_then(|bytes| str::from_utf8(bytes).ok())
+                .ok_or_else(|| error!(IdentityError::InvalidMessageFormat))?;
+            entries.push(LinkBatchEntry {
+                evm_address,
+                solana_address: solana_address.to_string(),
+                primary: false,
+            });
+            offset += 21 + len;
+        }
+        match &message[offset..] {
+            [] => {},
+            [primary_mask] if (*primary_mask as usize) >> count == 0 => {
+                for (index, entry) in entries.iter_mut().enumerate() {
+                    entry.primary = primary_mask & (1 << index) != 0;
+                }
+            },
+            _ => return Err(error!(IdentityError::InvalidMessageFormat)),
+        }
+
+        Ok(LinkBatch { timestamp, entries })
+    }
+}
+
+// AuthorityRotation layout:
+// type (1) | len (1) | old base58 Solana address (len) | len (1) | new base58 Solana address (len)
+//   | count (1) | count x evm_address (20)
+// Solana addresses are sent the way EVM stores them, as base58 strings, so it
+// can replace the old one in the links of every EVM address listed.
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct AuthorityRotation {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub evm_addresses: Vec<[u8; 20]>, // EVM addresses linked to the identity
+}
+
+impl AuthorityRotation {
+    pub fn encode(&self) -> Vec<u8> {
+        let old_authority = self.old_authority.to_string();
+        let new_authority = self.new_authority.to_string();
+        let mut message = vec![MessageType::AuthorityRotation as u8];
+        message.push(old_authority.len() as u8);
+        message.extend_from_slice(old_authority.as_bytes());
+        message.push(new_authority.len() as u8);
+        message.extend_from_slice(new_authority.as_bytes());
+        message.push(self.evm_addresses.len() as u8);
+        for evm_address in self.evm_addresses.iter() {
+            message.extend_from_slice(evm_address);
+        }
+        message
+    }
+}
+
+// Every chunk but the last carries exactly this many payload bytes, so chunks
+// can be written in place whatever order they arrive in
+pub const CHUNK_DATA_SIZE: usize = 512;
+// Most chunks a message can be split into
+pub const MAX_CHUNKS: usize = 16;
+
+// Chunk layout:
+// type (1) | message_id (32) | index (2) | total (2) | data
+// `message_id` is chosen by the sender and must be unique per sender. The
+// reassembled payload is any other message, starting with its own type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    pub message_id: [u8; 32],
+    pub index: u16,
+    pub total: u16,
+    pub data: Vec<u8>,
+}
+
+impl Chunk {
+    const HEADER_SIZE: usize = 1 + 32 + 2 + 2;
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::HEADER_SIZE + self.data.len());
+        message.push(MessageType::Chunk as u8);
+        message.extend_from_slice(&self.message_id);
+        message.extend_from_slice(&self.index.to_be_bytes());
+        message.extend_from_slice(&self.total.to_be_bytes());
+        message.extend_from_slice(&self.data);
+        message
+    }
+
+    pub fn decode(message: &[u8]) -> Result<Self> {
+        if message.len() < Self::HEADER_SIZE || message_type(message) != Some(MessageType::Chunk) {
+            return Err(error!(IdentityError::InvalidMessageFormat));
+        }
+        let mut message_id = [0u8; 32];
+        message_id.copy_from_slice(&message[1..33]);
+        let index = u16::from_be_bytes([message[33], message[34]]);
+        let total = u16::from_be_bytes([message[35], message[36]]);
+        let data = message[Self::HEADER_SIZE..].to_vec();
+
+        if total == 0 || total as usize > MAX_CHUNKS || index >= total {
+            return Err(error!(IdentityError::InvalidMessageFormat));
+        }
+        // Only the last chunk may be shorter
+        let is_last = index + 1 == total;
+        if data.is_empty()
+            || data.len() > CHUNK_DATA_SIZE
+            || (!is_last && data.len() != CHUNK_DATA_SIZE)
+        {
+            return Err(error!(IdentityError::InvalidMessageFormat));
+        }
+        Ok(Chunk { message_id, index, total, data })
+    }
+}
+
+// Split a payload into the chunk messages that carry it
+pub fn split_into_chunks(message_id: [u8; 32], payload: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let total = payload.len().div_ceil(CHUNK_DATA_SIZE);
+    if total == 0 || total > MAX_CHUNKS {
+        return Err(error!(IdentityError::InvalidMessageFormat));
+    }
+    Ok(payload
+        .chunks(CHUNK_DATA_SIZE)
+        .enumerate()
+        .map(|(index, data)| {
+            Chunk { message_id, index: index as u16, total: total as u16, data: data.to_vec() }
+                .encode()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_query_layout() {
+        let query =
+            LinksQuery { evm_address: [0x11; 20], compose_to: Pubkey::new_from_array([0x22; 32]) };
+        let mut expected = vec![1];
+        expected.extend_from_slice(&[0x11; 20]);
+        expected.extend_from_slice(&[0x22; 32]);
+        assert_eq!(query.encode(), expected);
+        assert_eq!(expected.len(), LinksQuery::SIZE);
+    }
+
+    #[test]
+    fn decodes_links_response() {
+        // What the EVM linker sends back for two linked Solana addresses
+        let mut message = vec![2];
+        message.extend_from_slice(&[0xaa; 32]);
+        message.extend_from_slice(&[0xbb; 32]);
+        message.extend_from_slice(&[0x11; 20]);
+        message.extend_from_slice(&[0x00, 0x02]);
+        message.push(3);
+        message.extend_from_slice(b"abc");
+        message.push(2);
+        message.extend_from_slice(b"de");
+
+        let response = LinksResponse::decode(&message).unwrap();
+        assert_eq!(
+            response,
+            LinksResponse {
+                request_guid: [0xaa; 32],
+                compose_to: Pubkey::new_from_array([0xbb; 32]),
+                evm_address: [0x11; 20],
+                solana_addresses: vec!["abc".to_string(), "de".to_string()],
+            }
+        );
+        assert_eq!(response.encode(), message);
+    }
+
+    #[test]
+    fn rejects_malformed_links_responses() {
+        let response = LinksResponse {
+            request_guid: [0xaa; 32],
+            compose_to: Pubkey::default(),
+            evm_address: [0x11; 20],
+            solana_addresses: vec!["abc".to_string()],
+        };
+        let message = response.encode();
+        // Truncated address, trailing bytes, wrong type, short header
+        assert!(LinksResponse::decode(&message[..message.len() - 1]).is_err());
+        assert!(LinksResponse::decode(&[message.clone(), vec![0]].concat()).is_err());
+        assert!(LinksResponse::decode(&[vec![1], message[1..].to_vec()].concat()).is_err());
+        assert!(LinksResponse::decode(&message[..LinksResponse::HEADER_SIZE - 1]).is_err());
+        // Typed messages never start like the legacy payloads
+        assert_eq!(message_type(&message), Some(MessageType::LinksResponse));
+        assert_eq!(message_type(b"0xab,So1,0"), None);
+        assert_eq!(message_type(&[0, 1, 2]), None);
+    }
+
+    #[test]
+    fn decodes_verify_query() {
+        let mut message = vec![3];
+        message.extend_from_slice(&[0x11; 20]);
+        message.extend_from_slice(&[0x22; 32]);
+        message.extend_from_slice(&1u64.to_be_bytes());
+        message.extend_from_slice(&5_000u64.to_be_bytes());
+
+        let query = VerifyQuery::decode(&message).unwrap();
+        assert_eq!(
+            query,
+            VerifyQuery {
+                evm_address: [0x11; 20],
+                solana_address: Pubkey::new_from_array([0x22; 32]),
+                chain_id: 1,
+                response_fee: 5_000,
+            }
+        );
+        assert_eq!(query.encode(), message);
+        assert!(VerifyQuery::decode(&message[..VerifyQuery::SIZE - 1]).is_err());
+        assert!(VerifyQuery::decode(&[message.clone(), vec![0]].concat()).is_err());
+    }
+
+    #[test]
+    fn verify_response_layout() {
+        let response = VerifyResponse {
+            request_guid: [0xaa; 32],
+            evm_address: [0x11; 20],
+            solana_address: Pubkey::new_from_array([0x22; 32]),
+            verification: Some(LinkVerification::Attested { attestor_id: 0x0102_0304 }),
+        };
+        let mut expected = vec![4];
+        expected.extend_from_slice(&[0xaa; 32]);
+        expected.extend_from_slice(&[0x11; 20]);
+        expected.extend_from_slice(&[0x22; 32]);
+        expected.extend_from_slice(&[LEVEL_ATTESTED, 0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(response.encode(), expected);
+        assert_eq!(expected.len(), VerifyResponse::SIZE);
+    }
+
+    #[test]
+    fn verification_levels() {
+        assert_eq!(verification_level(None), (LEVEL_NOT_LINKED, 0));
+        assert_eq!(
+            verification_level(Some(LinkVerification::SelfAsserted)),
+            (LEVEL_SELF_ASSERTED, 0)
+        );
+        assert_eq!(
+            verification_level(Some(LinkVerification::Downgraded { attestor_id: 7 })),
+            (LEVEL_DOWNGRADED, 7)
+        );
+        assert_eq!(
+            verification_level(Some(LinkVerification::Imported { import_id: 9 })),
+            (LEVEL_IMPORTED, 9)
+        );
+    }
+
+    #[test]
+    fn decodes_link_batch() {
+        // What `_linkBatchPayload` packs on EVM for two entries
+        let mut message = vec![5];
+        message.extend_from_slice(&1_700_000_000i64.to_be_bytes());
+        message.push(2);
+        message.extend_from_slice(&[0x11; 20]);
+        message.push(3);
+        message.extend_from_slice(b"abc");
+        message.extend_from_slice(&[0x11; 20]);
+        message.push(2);
+        message.extend_from_slice(b"de");
+
+        let batch = LinkBatch::decode(&message).unwrap();
+        assert_eq!(batch.timestamp, 1_700_000_000);
+        assert_eq!(
+            batch.entries,
+            vec![
+                LinkBatchEntry {
+                    evm_address: [0x11; 20],
+                    solana_address: "abc".to_string(),
+                    primary: false,
+                },
+                LinkBatchEntry {
+                    evm_address: [0x11; 20],
+                    solana_address: "de".to_string(),
+                    primary: false,
+                },
+            ]
+        );
+        assert_eq!(batch.encode(), message);
+        // Entries carry the Solana address as submitted, it is only parsed when applied
+        assert_eq!(batch.entries[0].solana_pubkey(), None);
+    }
+
+    #[test]
+    fn rejects_malformed_link_batches() {
+        let entry = LinkBatchEntry {
+            evm_address: [0x11; 20],
+            solana_address: Pubkey::new_from_array([0x22; 32]).to_string(),
+            primary: false,
+        };
+        let batch = LinkBatch { timestamp: 1, entries: vec![entry.clone(); MAX_BATCH_LINKS] };
+        let message = batch.encode();
+        assert_eq!(LinkBatch::decode(&message).unwrap(), batch);
+        assert!(LinkBatch::decode(&message[..message.len() - 1]).is_err());
+
+        let too_many = LinkBatch { timestamp: 1, entries: vec![entry; MAX_BATCH_LINKS + 1] };
+        assert!(LinkBatch::decode(&too_many.encode()).is_err());
+        assert!(LinkBatch::decode(&message[..LinkBatch::HEADER_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn chunk_layout() {
+        let chunk = Chunk { message_id: [0xaa; 32], index: 1, total: 2, data: vec![7; 10] };
+        let mut expected = vec![6];
+        expected.extend_from_slice(&[0xaa; 32]);
+        expected.extend_from_slice(&[0x00, 0x01, 0x00, 0x02]);
+        expected.extend_from_slice(&[7; 10]);
+        assert_eq!(chunk.encode(), expected);
+        assert_eq!(Chunk::decode(&expected).unwrap(), chunk);
+    }
+
+    #[test]
+    fn splits_and_reassembles_payloads() {
+        let payload: Vec<u8> = (0..CHUNK_DATA_SIZE * 2 + 3).map(|i| i as u8).collect();
+        let chunks = split_into_chunks([1; 32], &payload).unwrap();
+        assert_eq!(chunks.len(), 3);
+
+        let decoded: Vec<Chunk> =
+            chunks.iter().map(|chunk| Chunk::decode(chunk).unwrap()).collect();
+        assert!(decoded.iter().all(|chunk| chunk.total == 3 && chunk.message_id == [1; 32]));
+        assert_eq!(decoded[2].data.len(), 3);
+        let reassembled: Vec<u8> = decoded.iter().flat_map(|chunk| chunk.data.clone()).collect();
+        assert_eq!(reassembled, payload);
+
+        // A payload that fills its chunks exactly has no short last chunk
+        assert_eq!(split_into_chunks([1; 32], &payload[..CHUNK_DATA_SIZE]).unwrap().len(), 1);
+        assert!(split_into_chunks([1; 32], &[]).is_err());
+        assert!(split_into_chunks([1; 32], &vec![0; CHUNK_DATA_SIZE * MAX_CHUNKS + 1]).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_chunks() {
+        let chunk = |index: u16, total: u16, len: usize| {
+            Chunk { message_id: [0; 32], index, total, data: vec![0; len] }.encode()
+        };
+        // Only the last chunk may be short, and never empty
+        assert!(Chunk::decode(&chunk(0, 2, CHUNK_DATA_SIZE - 1)).is_err());
+        assert!(Chunk::decode(&chunk(1, 2, 0)).is_err());
+        assert!(Chunk::decode(&chunk(1, 2, CHUNK_DATA_SIZE + 1)).is_err());
+        // Index and total in range
+        assert!(Chunk::decode(&chunk(2, 2, 1)).is_err());
+        assert!(Chunk::decode(&chunk(0, 0, 1)).is_err());
+        assert!(Chunk::decode(&chunk(0, MAX_CHUNKS as u16 + 1, CHUNK_DATA_SIZE)).is_err());
+    }
+}
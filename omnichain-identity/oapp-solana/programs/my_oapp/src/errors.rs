@@ -4,7 +4,55 @@ use anchor_lang::prelude::error_code;
 pub enum MyOAppError {
     InvalidMessageType,
     InvalidPayload,
-    InvalidAddress,
-    IdentityAccountNotFound,
-    InvalidMessageFormat
+    TooManyAttestors,
+    AttestorNotFound,
+    AttestorRevoked,
+    AttestationExpired,
+    MissingAttestation,
+    InvalidDaoName,
+    InvalidChainReference,
+    SnapshotExpired,
+    AddressNotLinked,
+    InvalidMerkleProof,
+    ClaimIndexOutOfRange,
+    AlreadyClaimed,
+    InvalidPendingQuery,
+    QueryNotPending,
+    InvalidMessageAccounts,
+    ChunkMismatch,
+    ReassemblyIncomplete,
+    ReassemblyNotExpired,
+    SponsorshipQuotaExceeded,
+    SponsoredFeeTooHigh,
+    SponsorshipCapReached,
+    InsufficientTreasuryFunds,
+    EnforcedOptionsTooLong,
+    TooManyTrustedSenders,
+    PeerDisabled,
+    TooManyPeers,
+    PrimaryNotEvm,
+    InvalidPeer,
+    InvalidGuardians,
+    NotGuardian,
+    RecoveryMismatch,
+    NoPendingRecovery,
+    RecoveryTimelocked,
+    EvmManagerNotSet,
+    EvmManagerNotVerified,
+    IdentityFrozen,
+    InvalidLinkExpiry,
+    NothingToPrune,
+    InvalidDelegation,
+    DelegationExpired,
+    IdentityClosed,
+    InvalidCleanupAccount,
+    LinkNotPending,
+    InvalidQueryTimeout,
+    QueryNotExpired,
+    IdentityHasOpenGrants,
+    TooManyGrants,
+    IdentityNotLegacy,
+    MembershipClaimed,
+    SponsorshipNotEligible,
+    AttestorNotRevoked,
 }
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use identity_core::EvmCommand;
+
+// Outcome of one entry of a LinkBatch message
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum LinkBatchResult {
+    // Waiting for the identity's authority, see `review_pending_link`
+    Pending,
+    AlreadyLinked,
+    // The Solana wallet has no identity account, see `init_identity`
+    IdentityMissing,
+    InvalidSolanaAddress,
+    // The identity account was compacted by `prune_expired`; `add_linked_address`
+    // or `set_link_expiry` grows it back
+    IdentityTooSmall,
+    // The EVM manager froze the identity
+    IdentityFrozen,
+    AlreadyPending,
+    // The authority has to review the pending links first
+    TooManyPendingLinks,
+}
+
+#[event]
+pub struct LinkBatchEntryProcessed {
+    pub guid: [u8; 32], // guid of the LinkBatch message
+    pub index: u8,
+    pub evm_address: [u8; 20],
+    pub solana_address: String,
+    pub result: LinkBatchResult,
+}
+
+#[event]
+pub struct SponsoredFeePaid {
+    pub user: Pubkey,
+    pub dst_eid: u32,
+    pub guid: [u8; 32],
+    pub fee: u64, // Native fee paid by the treasury
+}
+
+#[event]
+pub struct AuthorityRotated {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub linked_addresses: u8,      // Links moved to the new identity account
+    pub notified_eid: Option<u32>, // EVM peer told about the rotation, if any
+}
+
+#[event]
+pub struct IdentityRecovered {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct EvmCommandExecuted {
+    pub authority: Pubkey, // Authority of the identity when the command was signed
+    pub evm_manager: [u8; 20],
+    pub nonce: u64,
+    pub command: EvmCommand,
+}
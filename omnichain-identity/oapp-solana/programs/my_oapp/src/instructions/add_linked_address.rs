@@ -3,50 +3,71 @@ use anchor_lang::prelude::*;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct AddLinkedAddressParams {
-    pub evm_address: String,
+    pub account_id: String, // CAIP-10 account id, e.g. eip155:1:0xabc... (eip155:0:... for any EVM chain)
 }
 
 #[derive(Accounts)]
 #[instruction(params: AddLinkedAddressParams)]
 pub struct AddLinkedAddress<'info> {
     #[account(mut)]
-    pub authority: Signer<'info>,
+    /// The identity's authority, or a delegate holding `delegation`
+    pub signer: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [IDENTITY_SEED, authority.key().as_ref()],
+        seeds = [IDENTITY_SEED, identity_account.authority.as_ref()],
         bump = identity_account.bump,
-        constraint = identity_account.authority == authority.key() @ MyOAppError::InvalidAddress
+        constraint = !identity_account.frozen @ MyOAppError::IdentityFrozen,
+        realloc = IdentityAccount::SIZE,
+        realloc::payer = signer,
+        realloc::zero = false
     )]
     pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        seeds = [DELEGATION_SEED, identity_account.key().as_ref(), signer.key().as_ref()],
+        bump = delegation.bump
+    )]
+    /// Grant of the delegate, only needed when the signer isn't the authority
+    pub delegation: Option<Account<'info, Delegation>>,
+
+    pub system_program: Program<'info, System>,
 }
 
 impl AddLinkedAddress<'_> {
     pub fn apply(ctx: &mut Context<AddLinkedAddress>, params: &AddLinkedAddressParams) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let delegation = ctx.accounts.delegation.as_deref();
         let identity_account = &mut ctx.accounts.identity_account;
+        identity_account.check_manager(
+            &ctx.accounts.signer.key(),
+            delegation,
+            DelegationScope::Add,
+            now,
+        )?;
         
-        // Validate EVM address format
-        if !identity_msg_codec::is_valid_evm_address(&params.evm_address) {
-            msg!("Invalid EVM address format");
-            return Err(error!(MyOAppError::InvalidAddress));
-        }
+        // Parse the CAIP-10 id, validating the address with its namespace's rules
+        let link = identity_core::parse_account_id(&params.account_id)?;
+
+        // Expired links make room and can be linked again
+        identity_account.prune_expired(now);
         
-        // Check if this address is already linked
-        if identity_account.linked_addresses.contains(&params.evm_address) {
-            msg!("This EVM address is already linked to this account");
+        // Check if this address is already linked on this chain (or on every chain)
+        if identity_account.linked_addresses.iter().any(|linked| linked.subsumes(&link)) {
+            msg!("{} is already linked to this account", params.account_id);
             return Ok(());
         }
         
         // Check if we're exceeding the maximum number of linked addresses
         if identity_account.linked_addresses.len() >= IdentityAccount::MAX_ADDRESSES {
             msg!("Maximum number of linked addresses reached");
-            return Err(error!(MyOAppError::InvalidPayload));
+            return Err(error!(IdentityError::TooManyLinkedAddresses));
         }
         
-        // Add the new EVM address to the list
-        identity_account.linked_addresses.push(params.evm_address.clone());
+        // Add the new address to the list
+        identity_account.linked_addresses.push(link);
         
-        msg!("Added new linked address for {}", ctx.accounts.authority.key());
+        msg!("Added new linked address for {}", identity_account.authority);
         Ok(())
     }
 }
@@ -0,0 +1,63 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// A guardian backs moving an identity to `new_authority`, replacing its
+// previous approval if any, or withdraws its approval with None. The timelock
+// starts once `threshold` guardians back the same new authority; the authority
+// can cancel until the recovery is executed.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ApproveRecoveryParams {
+    pub identity_authority: Pubkey, // Current authority of the identity being recovered
+    pub new_authority: Option<Pubkey>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: ApproveRecoveryParams)]
+pub struct ApproveRecovery<'info> {
+    pub guardian: Signer<'info>,
+
+    #[account(
+        seeds = [IDENTITY_SEED, params.identity_authority.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        seeds = [RECOVERY_SEED, identity_account.key().as_ref()],
+        bump = recovery.bump
+    )]
+    pub recovery: Account<'info, RecoveryConfig>,
+}
+
+impl ApproveRecovery<'_> {
+    pub fn apply(ctx: &mut Context<ApproveRecovery>, params: &ApproveRecoveryParams) -> Result<()> {
+        let recovery = &mut ctx.accounts.recovery;
+        let index = recovery
+            .guardian_index(&ctx.accounts.guardian.key())
+            .ok_or_else(|| error!(MyOAppError::NotGuardian))?;
+        if params.new_authority == Some(params.identity_authority) {
+            return Err(error!(MyOAppError::RecoveryMismatch));
+        }
+
+        recovery.vote(index, params.new_authority, Clock::get()?.unix_timestamp);
+
+        match params.new_authority {
+            Some(new_authority) => msg!(
+                "Recovery of {} to {} approved by {} ({}/{})",
+                params.identity_authority,
+                new_authority,
+                ctx.accounts.guardian.key(),
+                recovery.votes_for(&new_authority),
+                recovery.threshold
+            ),
+            None => msg!(
+                "Recovery approval for {} withdrawn by {}",
+                params.identity_authority,
+                ctx.accounts.guardian.key()
+            ),
+        }
+        Ok(())
+    }
+}
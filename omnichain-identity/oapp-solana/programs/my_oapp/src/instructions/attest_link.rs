@@ -0,0 +1,98 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked, ID as INSTRUCTIONS_SYSVAR_ID,
+};
+use identity_core::LinkVerification;
+
+// Adds a link vouched for by a trusted attestor. The transaction must contain,
+// right before this instruction, an Ed25519 program instruction verifying the
+// attestor's signature over `identity_core::attestation_message`, for the
+// identity's current attestation nonce. Anyone can submit the attestation,
+// e.g. the attestor itself or a relayer.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AttestLinkParams {
+    pub authority: Pubkey,  // Solana wallet the link is attested for
+    pub account_id: String, // CAIP-10 account id being attested
+    pub attestor_id: u32,
+    pub expires_at: i64, // Unix timestamp after which the attestation can't be used
+}
+
+#[derive(Accounts)]
+#[instruction(params: AttestLinkParams)]
+pub struct AttestLink<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    /// CHECK: the identity of `params.authority`, created if it doesn't exist yet,
+    /// see `IdentityAccount::load_or_create`
+    #[account(mut, seeds = [IDENTITY_SEED, params.authority.as_ref()], bump)]
+    pub identity_account: UncheckedAccount<'info>,
+    /// CHECK: the tombstone of a closed identity, which blocks creating it again
+    #[account(seeds = [TOMBSTONE_SEED, params.authority.as_ref()], bump)]
+    pub tombstone: UncheckedAccount<'info>,
+    /// CHECK: the instructions sysvar, used to read the Ed25519 instruction
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl AttestLink<'_> {
+    pub fn apply(ctx: &mut Context<AttestLink>, params: &AttestLinkParams) -> Result<()> {
+        let attestor = ctx.accounts.store.attestor(params.attestor_id)?;
+        if attestor.revoked {
+            return Err(error!(MyOAppError::AttestorRevoked));
+        }
+        if Clock::get()?.unix_timestamp > params.expires_at {
+            return Err(error!(MyOAppError::AttestationExpired));
+        }
+
+        let identity_info = ctx.accounts.identity_account.to_account_info();
+        let mut identity_account = IdentityAccount::load_or_create(
+            &ctx.accounts.payer.to_account_info(),
+            &identity_info,
+            &ctx.accounts.tombstone.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            params.authority,
+            ctx.bumps.identity_account,
+        )?;
+
+        // The attestor's signature is verified by the Ed25519 instruction just before this one
+        let instructions = ctx.accounts.instructions.to_account_info();
+        let current_index = load_current_index_checked(&instructions)?;
+        if current_index == 0 {
+            return Err(error!(MyOAppError::MissingAttestation));
+        }
+        let ed25519_ix = load_instruction_at_checked(current_index as usize - 1, &instructions)?;
+        let message = identity_core::attestation_message(
+            &crate::ID,
+            &params.authority,
+            identity_account.attestation_nonce,
+            &params.account_id,
+            params.expires_at,
+        );
+        identity_core::verify_ed25519_instruction(&ed25519_ix, &attestor.key, &message)?;
+        // Uses up the attestation, so it can't bring the link back once removed
+        identity_account.attestation_nonce += 1;
+
+        let mut link = identity_core::parse_account_id(&params.account_id)?;
+        link.verification = LinkVerification::Attested { attestor_id: params.attestor_id };
+
+        // Upgrade the same link if it already exists, otherwise add it. An
+        // expired link is pruned first, so it comes back without an expiry.
+        identity_account.prune_expired(Clock::get()?.unix_timestamp);
+        identity_account.record_verified_link(link)?;
+        identity_account.try_serialize(&mut &mut identity_info.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "Attestor {} attested {} for {}, nonce {}",
+            params.attestor_id,
+            params.account_id,
+            params.authority,
+            identity_account.attestation_nonce - 1
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// The authority drops a pending recovery, whether it is still collecting
+// approvals or waiting out its timelock, along with every guardian's approval.
+// Guardians stay registered.
+
+#[derive(Accounts)]
+pub struct CancelRecovery<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [IDENTITY_SEED, authority.key().as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.authority == authority.key() @ IdentityError::InvalidAddress,
+        constraint = !identity_account.frozen @ MyOAppError::IdentityFrozen
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        seeds = [RECOVERY_SEED, identity_account.key().as_ref()],
+        bump = recovery.bump
+    )]
+    pub recovery: Account<'info, RecoveryConfig>,
+}
+
+impl CancelRecovery<'_> {
+    pub fn apply(ctx: &mut Context<CancelRecovery>) -> Result<()> {
+        let recovery = &mut ctx.accounts.recovery;
+        if recovery.pending.is_none() && recovery.votes.iter().all(Option::is_none) {
+            return Err(error!(MyOAppError::NoPendingRecovery));
+        }
+        let pending = recovery.pending.take();
+        recovery.votes.clear();
+
+        match pending {
+            Some(pending) => msg!(
+                "Recovery of {} to {} cancelled",
+                ctx.accounts.authority.key(),
+                pending.new_authority
+            ),
+            None => msg!("Recovery approvals for {} cancelled", ctx.accounts.authority.key()),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use identity_core::{ChainAddress, LinkVerification, LinkedAddress};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ClaimImportedLinkParams {
+    pub index: u64,            // Position of the entry in the import
+    pub evm_address: [u8; 20], // EVM address paired with the claiming wallet
+    pub proof: Vec<[u8; 32]>,  // Sibling hashes from the leaf up to the root
+}
+
+#[derive(Accounts)]
+#[instruction(params: ClaimImportedLinkParams)]
+pub struct ClaimImportedLink<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub link_import: Account<'info, LinkImport>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = ImportClaims::SIZE,
+        seeds = [
+            IMPORT_CLAIMS_SEED,
+            link_import.key().as_ref(),
+            &ImportClaims::chunk_of(params.index).to_le_bytes()
+        ],
+        bump,
+    )]
+    pub import_claims: Box<Account<'info, ImportClaims>>,
+    /// CHECK: the identity of `authority`, created if it doesn't exist yet,
+    /// see `IdentityAccount::load_or_create`
+    #[account(mut, seeds = [IDENTITY_SEED, authority.key().as_ref()], bump)]
+    pub identity_account: UncheckedAccount<'info>,
+    /// CHECK: the tombstone of a closed identity, which blocks creating it again
+    #[account(seeds = [TOMBSTONE_SEED, authority.key().as_ref()], bump)]
+    pub tombstone: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl ClaimImportedLink<'_> {
+    pub fn apply(
+        ctx: &mut Context<ClaimImportedLink>,
+        params: &ClaimImportedLinkParams,
+    ) -> Result<()> {
+        let link_import = &ctx.accounts.link_import;
+        if params.index >= link_import.entry_count {
+            return Err(error!(MyOAppError::ClaimIndexOutOfRange));
+        }
+
+        let authority = ctx.accounts.authority.key();
+        let leaf = identity_core::import_leaf(params.index, &authority, &params.evm_address);
+        if !identity_core::verify_merkle_proof(&params.proof, &link_import.merkle_root, leaf) {
+            return Err(error!(MyOAppError::InvalidMerkleProof));
+        }
+
+        let import_claims = &mut ctx.accounts.import_claims;
+        // First claim in this chunk: the bitmap was just created
+        if import_claims.link_import == Pubkey::default() {
+            import_claims.link_import = link_import.key();
+            import_claims.chunk = ImportClaims::chunk_of(params.index);
+            import_claims.bump = ctx.bumps.import_claims;
+        }
+        if !import_claims.claim(params.index) {
+            return Err(error!(MyOAppError::AlreadyClaimed));
+        }
+
+        let identity_info = ctx.accounts.identity_account.to_account_info();
+        let mut identity_account = IdentityAccount::load_or_create(
+            &ctx.accounts.authority.to_account_info(),
+            &identity_info,
+            &ctx.accounts.tombstone.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            authority,
+            ctx.bumps.identity_account,
+        )?;
+
+        // Imported pairs weren't tied to a chain. An existing attested link is
+        // stronger than the import, so it is left as is, unless it expired.
+        identity_account.prune_expired(Clock::get()?.unix_timestamp);
+        let mut link = LinkedAddress::new(ChainAddress::Evm(params.evm_address), None);
+        link.verification = LinkVerification::Imported { import_id: link_import.import_id };
+        match identity_account.verification_of(&link) {
+            Some(LinkVerification::Attested { .. }) => {},
+            _ => identity_account.record_verified_link(link)?,
+        }
+        identity_account.try_serialize(&mut &mut identity_info.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "Claimed entry {} of link import {} for {}",
+            params.index,
+            link_import.import_id,
+            authority
+        );
+        Ok(())
+    }
+}
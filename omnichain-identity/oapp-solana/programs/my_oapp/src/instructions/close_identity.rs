@@ -0,0 +1,80 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Deletes an identity and refunds its rent to the authority. A tombstone is
+// left in its place, paid out of that refund, so a late attestation or import
+// claim can't bring the identity back; see `reopen_identity`.
+//
+// Accounts that only make sense with the identity are closed too when passed
+// in the remaining accounts, their rent also going to the authority: the
+// recovery config, delegations and DAO memberships. The identity counts its
+// delegations and recovery config and only closes once all of them are gone,
+// as they are found by the identity's address and would apply again to a
+// reopened identity.
+
+#[derive(Accounts)]
+pub struct CloseIdentity<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [IDENTITY_SEED, authority.key().as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.authority == authority.key() @ IdentityError::InvalidAddress,
+        constraint = !identity_account.frozen @ MyOAppError::IdentityFrozen
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    // May be left from an earlier identity of this wallet
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = IdentityTombstone::SIZE,
+        seeds = [TOMBSTONE_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub tombstone: Account<'info, IdentityTombstone>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CloseIdentity<'info> {
+    pub fn apply(ctx: &mut Context<'_, '_, 'info, 'info, CloseIdentity<'info>>) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        let identity = ctx.accounts.identity_account.key();
+        let authority_info = ctx.accounts.authority.to_account_info();
+
+        let identity_account = &mut ctx.accounts.identity_account;
+        for info in ctx.remaining_accounts.iter() {
+            if identity_account.close_grant_account(&identity, info, &authority_info)? {
+                continue;
+            }
+            if !info.is_writable || !is_membership_of(info, &authority) {
+                return Err(error!(MyOAppError::InvalidCleanupAccount));
+            }
+            pda::close_pda(info, &authority_info)?;
+        }
+        identity_account.assert_no_open_grants()?;
+
+        ctx.accounts.tombstone.record(
+            authority,
+            identity_account.attestation_nonce,
+            ctx.bumps.tombstone,
+        )?;
+
+        msg!(
+            "Identity of {} closed, with {} related accounts",
+            authority,
+            ctx.remaining_accounts.len()
+        );
+        Ok(())
+    }
+}
+
+// Whether `info` is a DAO membership of `authority`. `Account::try_from` also
+// checks that this program owns it.
+fn is_membership_of<'info>(info: &'info AccountInfo<'info>, authority: &Pubkey) -> bool {
+    Account::<Membership>::try_from(info).is_ok_and(|membership| membership.authority == *authority)
+}
@@ -0,0 +1,43 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Removes a remote chain for good, refunding the rent of its PeerConfig to the
+// admin and dropping its eid from the registry on Store.
+
+#[derive(Accounts)]
+#[instruction(params: ClosePeerParams)]
+pub struct ClosePeer<'info> {
+    #[account(mut, address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        close = admin,
+        seeds = [PEER_SEED, &store.key().to_bytes(), &params.remote_eid.to_be_bytes()],
+        bump = peer.bump
+    )]
+    pub peer: Account<'info, PeerConfig>,
+    #[account(
+        mut,
+        seeds = [STORE_SEED],
+        bump = store.bump,
+        realloc = Store::SIZE,
+        realloc::payer = admin,
+        realloc::zero = false
+    )]
+    pub store: Account<'info, Store>,
+    pub system_program: Program<'info, System>,
+}
+
+impl ClosePeer<'_> {
+    pub fn apply(ctx: &mut Context<ClosePeer>, params: &ClosePeerParams) -> Result<()> {
+        ctx.accounts.store.peer_eids.retain(|eid| *eid != params.remote_eid);
+        msg!("Closed peer for eid {}", params.remote_eid);
+        Ok(())
+    }
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ClosePeerParams {
+    pub remote_eid: u32,
+}
@@ -0,0 +1,31 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Closes a links query once its deadline has passed, answered or not, refunding
+// its rent to the requester who paid for it. Anyone can run it. A response
+// arriving afterwards is dropped by `lz_receive`.
+
+#[derive(Accounts)]
+pub struct ClosePendingQuery<'info> {
+    #[account(
+        mut,
+        close = requester,
+        seeds = [PENDING_QUERY_SEED, &pending_query.guid],
+        bump = pending_query.bump
+    )]
+    pub pending_query: Account<'info, PendingQuery>,
+    /// CHECK: receives the rent of the query, checked against the requester recorded in it
+    #[account(mut, address = pending_query.requester)]
+    pub requester: UncheckedAccount<'info>,
+}
+
+impl ClosePendingQuery<'_> {
+    pub fn apply(ctx: &mut Context<ClosePendingQuery>) -> Result<()> {
+        let pending_query = &ctx.accounts.pending_query;
+        if !pending_query.is_expired(Clock::get()?.unix_timestamp) {
+            return Err(error!(MyOAppError::QueryNotExpired));
+        }
+        msg!("Closed links query {:?} ({:?})", pending_query.guid, pending_query.status);
+        Ok(())
+    }
+}
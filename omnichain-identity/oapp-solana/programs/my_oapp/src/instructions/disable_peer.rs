@@ -0,0 +1,40 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Stops all traffic with a remote chain without losing its configuration.
+// Inbound messages fail until the peer is enabled again, so they can still be
+// retried then.
+
+#[derive(Accounts)]
+#[instruction(params: DisablePeerParams)]
+pub struct DisablePeer<'info> {
+    #[account(address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [PEER_SEED, &store.key().to_bytes(), &params.remote_eid.to_be_bytes()],
+        bump = peer.bump
+    )]
+    pub peer: Account<'info, PeerConfig>,
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    pub store: Account<'info, Store>,
+}
+
+impl DisablePeer<'_> {
+    pub fn apply(ctx: &mut Context<DisablePeer>, params: &DisablePeerParams) -> Result<()> {
+        ctx.accounts.peer.disabled = params.disabled;
+        msg!(
+            "Peer for eid {} {}",
+            params.remote_eid,
+            if params.disabled { "disabled" } else { "enabled" }
+        );
+        Ok(())
+    }
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct DisablePeerParams {
+    pub remote_eid: u32,
+    pub disabled: bool, // false enables the peer again
+}
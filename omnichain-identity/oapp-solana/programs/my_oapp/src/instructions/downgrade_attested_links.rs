@@ -0,0 +1,53 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use identity_core::LinkVerification;
+
+// Permissionless crank applying attestor revocations: every identity account
+// passed in `remaining_accounts` (writable) has the links issued by revoked
+// attestors flagged with `downgrade_links`, or pruned ones, marked as
+// downgraded. As in `remove_link`, a downgraded primary link makes way for the
+// first verified EVM link and an EVM manager left without a verified link loses
+// its rights.
+
+#[derive(Accounts)]
+pub struct DowngradeAttestedLinks<'info> {
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    pub store: Account<'info, Store>,
+}
+
+impl<'info> DowngradeAttestedLinks<'info> {
+    pub fn apply(ctx: &Context<'_, '_, 'info, 'info, DowngradeAttestedLinks<'info>>) -> Result<()> {
+        let store = &ctx.accounts.store;
+        for account_info in ctx.remaining_accounts.iter() {
+            // Checks the account is an IdentityAccount owned by this program
+            let mut identity_account = Account::<IdentityAccount>::try_from(account_info)?;
+
+            let mut downgraded = 0;
+            for link in identity_account.linked_addresses.iter_mut() {
+                if let LinkVerification::Attested { attestor_id } = link.verification {
+                    if store.downgrades_links_of(attestor_id) {
+                        link.verification = LinkVerification::Downgraded { attestor_id };
+                        downgraded += 1;
+                    }
+                }
+            }
+
+            if downgraded > 0 {
+                let primary_downgraded = identity_account.primary_link().is_some_and(|link| {
+                    matches!(link.verification, LinkVerification::Downgraded { .. })
+                });
+                if primary_downgraded {
+                    identity_account.primary = identity_account.fallback_primary();
+                }
+                if let Some(manager) = identity_account.evm_manager {
+                    if !identity_account.has_verified_evm_link(&manager) {
+                        identity_account.evm_manager = None;
+                    }
+                }
+                identity_account.exit(&crate::ID)?;
+                msg!("Downgraded {} links of {}", downgraded, identity_account.authority);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use identity_core::{EvmCommand, EvmSignature};
+
+// Removes a link on behalf of the identity's EVM manager. Anyone can submit the
+// signed command, and it works while the identity is frozen.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EvmRemoveLinkParams {
+    pub authority: Pubkey,  // Current authority of the identity
+    pub account_id: String, // CAIP-10 account id, exactly as it is linked
+    pub signature: EvmSignature,
+}
+
+#[derive(Accounts)]
+#[instruction(params: EvmRemoveLinkParams)]
+pub struct EvmRemoveLink<'info> {
+    #[account(
+        mut,
+        seeds = [IDENTITY_SEED, params.authority.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+}
+
+impl EvmRemoveLink<'_> {
+    pub fn apply(ctx: &mut Context<EvmRemoveLink>, params: &EvmRemoveLinkParams) -> Result<()> {
+        let identity_account = &mut ctx.accounts.identity_account;
+        let nonce = identity_account.evm_nonce;
+        let command = EvmCommand::RemoveLink { account_id: params.account_id.clone() };
+        let evm_manager = identity_account.authorize_evm_command(
+            &command,
+            &params.signature,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let link = identity_core::parse_account_id(&params.account_id)?;
+        let index = identity_account
+            .position_of(&link)
+            .ok_or_else(|| error!(MyOAppError::AddressNotLinked))?;
+        identity_account.remove_link(index);
+
+        emit!(EvmCommandExecuted { authority: params.authority, evm_manager, nonce, command });
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use identity_core::{EvmCommand, EvmSignature};
+
+// Moves an identity to a new Solana wallet on behalf of its EVM manager, for
+// when the old wallet is lost or compromised. The new wallet signs and pays for
+// the new identity PDA, like in `rotate_authority`, and receives the rent of
+// the old one and of its recovery config. As the old wallet can't revoke its
+// delegations anymore, they go in the remaining accounts and are closed too,
+// all of them, next to the DAO memberships that move with the links. The old
+// wallet gets a tombstone and the moved identity keeps its EVM manager and is
+// unfrozen.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EvmRotateAuthorityParams {
+    pub authority: Pubkey, // Current authority of the identity
+    pub signature: EvmSignature,
+}
+
+#[derive(Accounts)]
+#[instruction(params: EvmRotateAuthorityParams)]
+pub struct EvmRotateAuthority<'info> {
+    #[account(mut)]
+    pub new_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = new_authority,
+        seeds = [IDENTITY_SEED, params.authority.as_ref()],
+        bump = old_identity.bump
+    )]
+    pub old_identity: Account<'info, IdentityAccount>,
+
+    #[account(
+        init,
+        payer = new_authority,
+        space = IdentityAccount::SIZE,
+        seeds = [IDENTITY_SEED, new_authority.key().as_ref()],
+        bump
+    )]
+    pub new_identity: Account<'info, IdentityAccount>,
+
+    // Keeps the old wallet from getting the identity back, see `close_identity`
+    #[account(
+        init_if_needed,
+        payer = new_authority,
+        space = IdentityTombstone::SIZE,
+        seeds = [TOMBSTONE_SEED, params.authority.as_ref()],
+        bump
+    )]
+    pub old_tombstone: Account<'info, IdentityTombstone>,
+
+    /// CHECK: tombstone of the new wallet, removed if it exists, see
+    /// `IdentityTombstone::clear_if_exists`
+    #[account(mut, seeds = [TOMBSTONE_SEED, new_authority.key().as_ref()], bump)]
+    pub new_tombstone: UncheckedAccount<'info>,
+
+    /// CHECK: recovery config of the old identity, closed if it exists, see
+    /// `RecoveryConfig::close_if_exists`
+    #[account(mut, seeds = [RECOVERY_SEED, old_identity.key().as_ref()], bump)]
+    pub old_recovery: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> EvmRotateAuthority<'info> {
+    pub fn apply(
+        ctx: &mut Context<'_, '_, 'info, 'info, EvmRotateAuthority<'info>>,
+        params: &EvmRotateAuthorityParams,
+    ) -> Result<()> {
+        let new_authority = ctx.accounts.new_authority.key();
+        let old_identity_key = ctx.accounts.old_identity.key();
+        let new_authority_info = ctx.accounts.new_authority.to_account_info();
+        let old_identity = &mut ctx.accounts.old_identity;
+        let nonce = old_identity.evm_nonce;
+        let command = EvmCommand::RotateAuthority { new_authority };
+        let evm_manager = old_identity.authorize_evm_command(
+            &command,
+            &params.signature,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        if RecoveryConfig::close_if_exists(
+            &ctx.accounts.old_recovery.to_account_info(),
+            &new_authority_info,
+        )? {
+            old_identity.close_grant();
+        }
+        for info in ctx.remaining_accounts.iter() {
+            if !old_identity.close_grant_account(&old_identity_key, info, &new_authority_info)?
+                && !Membership::move_if_of(info, &params.authority, &new_authority)?
+            {
+                return Err(error!(MyOAppError::InvalidCleanupAccount));
+            }
+        }
+        old_identity.assert_no_open_grants()?;
+        ctx.accounts.old_tombstone.record(
+            params.authority,
+            old_identity.attestation_nonce,
+            ctx.bumps.old_tombstone,
+        )?;
+
+        let cleared_tombstone = IdentityTombstone::clear_if_exists(
+            &ctx.accounts.new_tombstone.to_account_info(),
+            &new_authority_info,
+        )?;
+        let mut moved =
+            old_identity.moved_to(new_authority, ctx.bumps.new_identity, cleared_tombstone);
+        moved.frozen = false;
+        ctx.accounts.new_identity.set_inner(moved);
+
+        emit!(EvmCommandExecuted { authority: params.authority, evm_manager, nonce, command });
+        Ok(())
+    }
+}
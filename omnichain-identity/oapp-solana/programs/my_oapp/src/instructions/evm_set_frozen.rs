@@ -0,0 +1,43 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use identity_core::{EvmCommand, EvmSignature};
+
+// Freezes or unfreezes an identity on behalf of its EVM manager. A frozen
+// identity can't be changed by its Solana authority, which is meant for a hot
+// wallet that may be compromised; the EVM manager and guardian recovery keep
+// working.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EvmSetFrozenParams {
+    pub authority: Pubkey, // Current authority of the identity
+    pub frozen: bool,
+    pub signature: EvmSignature,
+}
+
+#[derive(Accounts)]
+#[instruction(params: EvmSetFrozenParams)]
+pub struct EvmSetFrozen<'info> {
+    #[account(
+        mut,
+        seeds = [IDENTITY_SEED, params.authority.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+}
+
+impl EvmSetFrozen<'_> {
+    pub fn apply(ctx: &mut Context<EvmSetFrozen>, params: &EvmSetFrozenParams) -> Result<()> {
+        let identity_account = &mut ctx.accounts.identity_account;
+        let nonce = identity_account.evm_nonce;
+        let command = EvmCommand::SetFrozen { frozen: params.frozen };
+        let evm_manager = identity_account.authorize_evm_command(
+            &command,
+            &params.signature,
+            Clock::get()?.unix_timestamp,
+        )?;
+        identity_account.frozen = params.frozen;
+
+        emit!(EvmCommandExecuted { authority: params.authority, evm_manager, nonce, command });
+        Ok(())
+    }
+}
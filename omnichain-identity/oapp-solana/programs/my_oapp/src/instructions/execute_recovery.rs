@@ -0,0 +1,119 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Completes a recovery once its timelock has passed. Like `rotate_authority`
+// the links move to a fresh identity PDA under the new wallet, which signs and
+// must not have an identity yet. The old identity and the recovery config are
+// closed to the new wallet, as the old key is presumed lost; guardians have to
+// be registered again for the new identity. For the same reason the old
+// identity's delegations go in the remaining accounts to be closed, all of them,
+// next to the DAO memberships that move with the links. The old wallet gets a
+// tombstone, so the lost key can't bring the identity back.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ExecuteRecoveryParams {
+    pub identity_authority: Pubkey, // Authority being recovered from
+}
+
+#[derive(Accounts)]
+#[instruction(params: ExecuteRecoveryParams)]
+pub struct ExecuteRecovery<'info> {
+    #[account(mut)]
+    pub new_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = new_authority,
+        seeds = [IDENTITY_SEED, params.identity_authority.as_ref()],
+        bump = old_identity.bump
+    )]
+    pub old_identity: Account<'info, IdentityAccount>,
+
+    #[account(
+        init,
+        payer = new_authority,
+        space = IdentityAccount::SIZE,
+        seeds = [IDENTITY_SEED, new_authority.key().as_ref()],
+        bump
+    )]
+    pub new_identity: Account<'info, IdentityAccount>,
+
+    // Keeps the old wallet from getting the identity back, see `close_identity`
+    #[account(
+        init_if_needed,
+        payer = new_authority,
+        space = IdentityTombstone::SIZE,
+        seeds = [TOMBSTONE_SEED, params.identity_authority.as_ref()],
+        bump
+    )]
+    pub old_tombstone: Account<'info, IdentityTombstone>,
+
+    /// CHECK: tombstone of the new wallet, removed if it exists, see
+    /// `IdentityTombstone::clear_if_exists`
+    #[account(mut, seeds = [TOMBSTONE_SEED, new_authority.key().as_ref()], bump)]
+    pub new_tombstone: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = new_authority,
+        seeds = [RECOVERY_SEED, old_identity.key().as_ref()],
+        bump = recovery.bump
+    )]
+    pub recovery: Account<'info, RecoveryConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ExecuteRecovery<'info> {
+    pub fn apply(
+        ctx: &mut Context<'_, '_, 'info, 'info, ExecuteRecovery<'info>>,
+        params: &ExecuteRecoveryParams,
+    ) -> Result<()> {
+        let new_authority = ctx.accounts.new_authority.key();
+        let pending = ctx
+            .accounts
+            .recovery
+            .pending
+            .ok_or_else(|| error!(MyOAppError::NoPendingRecovery))?;
+        if pending.new_authority != new_authority {
+            return Err(error!(MyOAppError::RecoveryMismatch));
+        }
+        if Clock::get()?.unix_timestamp < pending.executable_at {
+            return Err(error!(MyOAppError::RecoveryTimelocked));
+        }
+
+        // The recovery config is closed by its `close` constraint
+        let old_identity_key = ctx.accounts.old_identity.key();
+        let recovery_key = ctx.accounts.recovery.key();
+        let new_authority_info = ctx.accounts.new_authority.to_account_info();
+        let old_identity = &mut ctx.accounts.old_identity;
+        old_identity.close_grant();
+        for info in ctx.remaining_accounts.iter() {
+            if info.key() == recovery_key {
+                return Err(error!(MyOAppError::InvalidCleanupAccount));
+            }
+            if !old_identity.close_grant_account(&old_identity_key, info, &new_authority_info)?
+                && !Membership::move_if_of(info, &params.identity_authority, &new_authority)?
+            {
+                return Err(error!(MyOAppError::InvalidCleanupAccount));
+            }
+        }
+        old_identity.assert_no_open_grants()?;
+        ctx.accounts.old_tombstone.record(
+            params.identity_authority,
+            old_identity.attestation_nonce,
+            ctx.bumps.old_tombstone,
+        )?;
+
+        let old_authority = old_identity.authority;
+        let cleared_tombstone = IdentityTombstone::clear_if_exists(
+            &ctx.accounts.new_tombstone.to_account_info(),
+            &new_authority_info,
+        )?;
+        let moved = old_identity.moved_to(new_authority, ctx.bumps.new_identity, cleared_tombstone);
+        ctx.accounts.new_identity.set_inner(moved);
+
+        emit!(IdentityRecovered { old_authority, new_authority });
+        Ok(())
+    }
+}
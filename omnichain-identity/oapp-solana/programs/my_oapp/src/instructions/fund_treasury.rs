@@ -0,0 +1,44 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+#[derive(Accounts)]
+pub struct FundTreasury<'info> {
+    #[account(mut, address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = Treasury::SIZE,
+        seeds = [TREASURY_SEED],
+        bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+    pub system_program: Program<'info, System>,
+}
+
+impl FundTreasury<'_> {
+    pub fn apply(ctx: &mut Context<FundTreasury>, params: &FundTreasuryParams) -> Result<()> {
+        ctx.accounts.treasury.bump = ctx.bumps.treasury;
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.admin.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                },
+            ),
+            params.amount,
+        )?;
+        msg!("Funded treasury with {} lamports", params.amount);
+        Ok(())
+    }
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct FundTreasuryParams {
+    pub amount: u64,
+}
@@ -10,7 +10,7 @@ pub struct GetLinkedAddressesParams {
 #[instruction(params: GetLinkedAddressesParams)]
 pub struct GetLinkedAddresses<'info> {
     #[account(
-        seeds = [b"identity", params.solana_address.as_ref()],
+        seeds = [IDENTITY_SEED, params.solana_address.as_ref()],
         bump,
     )]
     pub identity_account: Account<'info, IdentityAccount>,
@@ -18,7 +18,15 @@ pub struct GetLinkedAddresses<'info> {
 
 impl GetLinkedAddresses<'_> {
     pub fn apply(ctx: &Context<GetLinkedAddresses>, _params: &GetLinkedAddressesParams) -> Result<Vec<String>> {
-        // Return the list of linked addresses
-        Ok(ctx.accounts.identity_account.linked_addresses.clone())
+        // Return the list of linked addresses as CAIP-10 account ids, with addresses
+        // formatted the way wallets show them (EVM addresses are EIP-55 checksummed).
+        // Expired links are left out even before they are pruned.
+        let now = Clock::get()?.unix_timestamp;
+        Ok(ctx
+            .accounts
+            .identity_account
+            .active_links(now)
+            .map(identity_core::format_account_id)
+            .collect())
     }
 }
@@ -0,0 +1,68 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Lets a delegate change the identity's links within `scope` until
+// `expires_at`, see `Delegation`. Granting again replaces the grant.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GrantDelegationParams {
+    pub delegate: Pubkey,
+    pub scope: DelegationScope,
+    pub expires_at: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: GrantDelegationParams)]
+pub struct GrantDelegation<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [IDENTITY_SEED, authority.key().as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.authority == authority.key() @ IdentityError::InvalidAddress,
+        constraint = !identity_account.frozen @ MyOAppError::IdentityFrozen
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = Delegation::SIZE,
+        seeds = [DELEGATION_SEED, identity_account.key().as_ref(), params.delegate.as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl GrantDelegation<'_> {
+    pub fn apply(ctx: &mut Context<GrantDelegation>, params: &GrantDelegationParams) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        if params.delegate == authority || params.expires_at <= Clock::get()?.unix_timestamp {
+            return Err(error!(MyOAppError::InvalidDelegation));
+        }
+
+        let delegation = &mut ctx.accounts.delegation;
+        // A new grant, rather than one being replaced
+        if delegation.identity == Pubkey::default() {
+            ctx.accounts.identity_account.open_grant()?;
+        }
+        delegation.identity = ctx.accounts.identity_account.key();
+        delegation.delegate = params.delegate;
+        delegation.scope = params.scope;
+        delegation.expires_at = params.expires_at;
+        delegation.bump = ctx.bumps.delegation;
+
+        msg!(
+            "{} delegated {:?} to {} until {}",
+            authority,
+            params.scope,
+            params.delegate,
+            params.expires_at
+        );
+        Ok(())
+    }
+}
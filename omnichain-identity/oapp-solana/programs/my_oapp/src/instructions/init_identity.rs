@@ -12,12 +12,20 @@ pub struct InitIdentity<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + IdentityAccount::SIZE,
+        space = IdentityAccount::SIZE,
         seeds = [IDENTITY_SEED, authority.key().as_ref()],
         bump,
     )]
     pub identity_account: Account<'info, IdentityAccount>,
 
+    /// CHECK: must not exist, see `reopen_identity` for wallets whose identity was closed
+    #[account(
+        seeds = [TOMBSTONE_SEED, authority.key().as_ref()],
+        bump,
+        constraint = tombstone.data_is_empty() @ MyOAppError::IdentityClosed
+    )]
+    pub tombstone: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
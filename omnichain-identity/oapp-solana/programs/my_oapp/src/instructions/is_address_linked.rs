@@ -4,7 +4,7 @@ use anchor_lang::prelude::*;
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct IsAddressLinkedParams {
     pub solana_address: Pubkey,
-    pub evm_address: String,
+    pub account_id: String, // CAIP-10 account id; eip155:0:... asks about any EVM chain
 }
 
 #[derive(Accounts)]
@@ -19,8 +19,13 @@ pub struct IsAddressLinked<'info> {
 
 impl IsAddressLinked<'_> {
     pub fn apply(ctx: &Context<IsAddressLinked>, params: &IsAddressLinkedParams) -> Result<bool> {
-        // Check if the EVM address is in the linked addresses list
-        let is_linked = ctx.accounts.identity_account.linked_addresses.contains(&params.evm_address);
+        // Compare validated bytes so any accepted spelling of the address matches
+        let query = identity_core::parse_account_id(&params.account_id)?;
+
+        // Check if a link for this address covers the queried chain, ignoring expired ones
+        let now = Clock::get()?.unix_timestamp;
+        let is_linked =
+            ctx.accounts.identity_account.active_links(now).any(|linked| linked.covers(&query));
         
         // Return the result
         Ok(is_linked)
@@ -3,11 +3,15 @@ use anchor_lang::prelude::*;
 use oapp::{
     endpoint::{
         cpi::accounts::Clear,
-        instructions::ClearParams,
+        instructions::{ClearParams, SendComposeParams},
         ConstructCPIContext, ID as ENDPOINT_ID,
     },
     LzReceiveParams,
 };
+use identity_core::{
+    ChainAddress, Chunk, LinkBatch, LinkBatchEntry, LinkedAddress, LinksResponse, MessageType,
+    PendingLink, VerifyQuery,
+};
 
 #[derive(Accounts)]
 #[instruction(params: LzReceiveParams)]
@@ -17,17 +21,22 @@ pub struct LzReceive<'info> {
     /// Customize the fields in `Store` as needed.
     #[account(mut, seeds = [STORE_SEED], bump = store.bump)]
     pub store: Account<'info, Store>,
-    /// Peer config PDA for the sending chain. Ensures `params.sender` can only be the allowed peer from that remote chain.
+    /// Peer config PDA for the sending chain. Ensures `params.sender` can only be the peer
+    /// or one of the trusted senders from that remote chain, and that the peer is enabled.
     #[account(
         seeds = [PEER_SEED, &store.key().to_bytes(), &params.src_eid.to_be_bytes()],
         bump = peer.bump,
-        constraint = params.sender == peer.peer_address
+        constraint = peer.is_trusted_sender(&params.sender, Clock::get()?.unix_timestamp),
+        constraint = !peer.disabled @ MyOAppError::PeerDisabled
     )]
     pub peer: Account<'info, PeerConfig>
 }
 
-impl LzReceive<'_> {
-    pub fn apply(ctx: &mut Context<LzReceive>, params: &LzReceiveParams) -> Result<()> {
+impl<'info> LzReceive<'info> {
+    pub fn apply(
+        ctx: &mut Context<'_, '_, '_, 'info, LzReceive<'info>>,
+        params: &LzReceiveParams,
+    ) -> Result<()> {
         // The OApp Store PDA is used to sign the CPI to the Endpoint program.
         let seeds: &[&[u8]] = &[STORE_SEED, &[ctx.accounts.store.bump]];
 
@@ -52,59 +61,93 @@ impl LzReceive<'_> {
             },
         )?;
 
-        // Try to decode the standard string message
-        match msg_codec::decode(&params.message) {
-            Ok(string_value) => {
-                let store = &mut ctx.accounts.store;
-                store.string = string_value;
-                
-                // If it's a standard string message, just process it normally
-                return Ok(());
-            },
-            Err(_) => {
-                // If it fails, try to decode it as an identity message
-                match identity_msg_codec::decode_identity_message(&params.message) {
-                    Ok(identity_msg) => {
-                        msg!("Identity message decoded successfully");
-                        msg!("EVM Address: {}", identity_msg.evm_address);
-                        msg!("Solana Address: {}", identity_msg.solana_address);
-                        
-                        // Process the identity message
-                        process_identity_message(ctx, &identity_msg)?;
-                    },
-                    Err(err) => {
-                        // Failed to decode as identity message too
-                        msg!("Failed to decode message format");
-                        return Err(err);
-                    }
-                }
-            }
+        // Chunks are stored until the whole message is there, see `process_chunk`
+        if identity_core::message_type(&params.message) == Some(MessageType::Chunk) {
+            return process_chunk(ctx, params);
         }
 
-        Ok(())
+        let inbound = InboundMessage {
+            src_eid: params.src_eid,
+            guid: params.guid,
+            message: &params.message,
+        };
+        dispatch_message(
+            &mut ctx.accounts.store,
+            &ctx.remaining_accounts[Clear::MIN_ACCOUNTS_LEN..],
+            &inbound,
+        )
     }
 }
 
-// Process an identity linking message and try to update the identity PDA if it exists
-fn process_identity_message(_ctx: &Context<LzReceive>, identity_msg: &identity_msg_codec::IdentityMessage) -> Result<()> {
-    // Validate the EVM address format
-    if !identity_msg_codec::is_valid_evm_address(&identity_msg.evm_address) {
-        msg!("Invalid EVM address format");
-        return Err(error!(MyOAppError::InvalidAddress));
+// A message to handle, either received by `lz_receive` or reassembled from
+// chunks. Messages reassembled from chunks use the message id as guid.
+pub struct InboundMessage<'a> {
+    pub src_eid: u32,
+    pub guid: [u8; 32],
+    pub message: &'a [u8],
+}
+
+// Handle a message with the accounts that follow the ones for `Endpoint::clear`
+pub fn dispatch_message<'info>(
+    store: &mut Account<'info, Store>,
+    accounts: &[AccountInfo<'info>],
+    inbound: &InboundMessage,
+) -> Result<()> {
+    // Typed messages are tagged by their first byte
+    match identity_core::message_type(inbound.message) {
+        Some(MessageType::LinksResponse) => return process_links_response(store, accounts, inbound),
+        Some(MessageType::VerifyQuery) => return process_verify_query(accounts, inbound),
+        Some(MessageType::LinkBatch) => return process_link_batch(accounts, inbound),
+        Some(_) => return Err(error!(MyOAppError::InvalidMessageType)),
+        None => {},
     }
 
+    // Try to decode the standard string message
+    match msg_codec::decode(inbound.message) {
+        Ok(string_value) => {
+            store.string = string_value;
+
+            // If it's a standard string message, just process it normally
+            return Ok(());
+        },
+        Err(_) => {
+            // If it fails, try to decode it as an identity message
+            match identity_core::decode_identity_message(inbound.message) {
+                Ok(identity_msg) => {
+                    msg!("Identity message decoded successfully");
+                    msg!("Linked Address: {}", identity_core::format_account_id(&identity_msg.linked_address));
+                    msg!("Solana Address: {}", identity_msg.solana_address);
+
+                    // Process the identity message
+                    process_identity_message(&identity_msg)?;
+                },
+                Err(err) => {
+                    // Failed to decode as identity message too
+                    msg!("Failed to decode message format");
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Process an identity linking message and try to update the identity PDA if it exists
+fn process_identity_message(identity_msg: &identity_core::IdentityMessage) -> Result<()> {
+    // The linked address was already validated against its namespace while decoding.
     // Try to parse the Solana address
-    let solana_pubkey = match identity_msg.solana_address.parse::<Pubkey>() {
+    let solana_pubkey = match identity_msg.solana_pubkey() {
         Ok(pubkey) => pubkey,
-        Err(_) => {
+        Err(err) => {
             msg!("Invalid Solana address format");
-            return Err(error!(MyOAppError::InvalidAddress));
+            return Err(err);
         }
     };
     
     // Log the information we received
     msg!("Processing identity link between:");
-    msg!("  EVM Address: {}", identity_msg.evm_address);
+    msg!("  Account: {}", identity_core::format_account_id(&identity_msg.linked_address));
     msg!("  Solana Address: {}", solana_pubkey);
     msg!("  Timestamp: {}", identity_msg.timestamp);
 
@@ -124,3 +167,300 @@ fn process_identity_message(_ctx: &Context<LzReceive>, identity_msg: &identity_m
     Ok(())
 }
 
+// Fill the PendingQuery a LinksResponse answers. The first message account is
+// the PendingQuery, then come the accounts for `Endpoint::send_compose` when
+// the requester asked to be notified.
+fn process_links_response<'info>(
+    store: &Account<'info, Store>,
+    accounts: &[AccountInfo<'info>],
+    inbound: &InboundMessage,
+) -> Result<()> {
+    let response = LinksResponse::decode(inbound.message)?;
+    let pending_query_info = accounts
+        .first()
+        .ok_or_else(|| error!(MyOAppError::InvalidPendingQuery))?;
+    let (pending_query_key, _) =
+        Pubkey::find_program_address(&[PENDING_QUERY_SEED, &response.request_guid], &crate::ID);
+    if pending_query_info.key() != pending_query_key {
+        return Err(error!(MyOAppError::InvalidPendingQuery));
+    }
+    // Closed by `close_pending_query` after its deadline: the message was
+    // cleared already, so the response is dropped
+    if pending_query_info.data_is_empty() {
+        msg!("Links query {:?} was closed, response dropped", response.request_guid);
+        return Ok(());
+    }
+    if pending_query_info.owner != &crate::ID {
+        return Err(error!(MyOAppError::InvalidPendingQuery));
+    }
+    let mut pending_query =
+        PendingQuery::try_deserialize(&mut &pending_query_info.try_borrow_data()?[..])?;
+
+    // The response must come from the chain the query went to, for the queried account
+    if pending_query.guid != response.request_guid
+        || pending_query.dst_eid != inbound.src_eid
+        || pending_query.evm_address != response.evm_address
+        || pending_query.compose_to != response.compose_to
+    {
+        return Err(error!(MyOAppError::InvalidPendingQuery));
+    }
+    if pending_query.status != QueryStatus::Pending {
+        return Err(error!(MyOAppError::QueryNotPending));
+    }
+
+    if pending_query.is_expired(Clock::get()?.unix_timestamp) {
+        pending_query.status = QueryStatus::TimedOut;
+        msg!("Links query response arrived after the deadline");
+    } else {
+        // Addresses the EVM side accepted without validation are skipped
+        pending_query.solana_addresses = response
+            .solana_addresses
+            .iter()
+            .filter_map(|address| address.parse::<Pubkey>().ok())
+            .take(PendingQuery::MAX_RESULTS)
+            .collect();
+        pending_query.status = QueryStatus::Fulfilled;
+        msg!("Links query fulfilled with {} addresses", pending_query.solana_addresses.len());
+    }
+    pending_query.try_serialize(&mut &mut pending_query_info.try_borrow_mut_data()?[..])?;
+
+    if pending_query.compose_to != Pubkey::default() {
+        let seeds: &[&[u8]] = &[STORE_SEED, &[store.bump]];
+        oapp::endpoint_cpi::send_compose(
+            ENDPOINT_ID,
+            store.key(),
+            &accounts[1..],
+            seeds,
+            SendComposeParams {
+                to: pending_query.compose_to,
+                guid: inbound.guid,
+                index: 0,
+                message: pending_query.guid.to_vec(),
+            },
+        )?;
+    }
+    Ok(())
+}
+
+// Answer a VerifyQuery from the identity account of the queried wallet. The
+// answer is stored in a VerificationResponse PDA and sent back separately by
+// `send_verification_response`, since sending needs accounts `lz_receive_types`
+// can't list. The message accounts are: the executor as payer, the identity
+// account (may not exist), the VerificationResponse PDA and the system program.
+fn process_verify_query(accounts: &[AccountInfo], inbound: &InboundMessage) -> Result<()> {
+    let query = VerifyQuery::decode(inbound.message)?;
+    let [payer, identity_info, response_info, system_program] = accounts else {
+        return Err(error!(MyOAppError::InvalidMessageAccounts));
+    };
+
+    let (identity_key, _) = Pubkey::find_program_address(
+        &[IDENTITY_SEED, query.solana_address.as_ref()],
+        &crate::ID,
+    );
+    let (response_key, response_bump) =
+        Pubkey::find_program_address(&[VERIFICATION_RESPONSE_SEED, &inbound.guid], &crate::ID);
+    if !payer.is_signer
+        || identity_info.key() != identity_key
+        || response_info.key() != response_key
+        || system_program.key() != System::id()
+    {
+        return Err(error!(MyOAppError::InvalidMessageAccounts));
+    }
+
+    // A wallet without an identity account has no links
+    let verification = if identity_info.owner == &crate::ID && !identity_info.data_is_empty() {
+        let identity_account =
+            IdentityAccount::try_deserialize(&mut &identity_info.try_borrow_data()?[..])?;
+        let chain_reference = (query.chain_id != 0).then(|| query.chain_id.to_string());
+        let link = LinkedAddress::new(ChainAddress::Evm(query.evm_address), chain_reference);
+        identity_account
+            .active_links(Clock::get()?.unix_timestamp)
+            .filter(|linked| linked.covers(&link))
+            .map(|linked| linked.verification)
+            .max_by_key(|verification| verification.strength())
+    } else {
+        None
+    };
+
+    pda::create_pda(
+        payer.clone(),
+        response_info.clone(),
+        system_program.clone(),
+        &[VERIFICATION_RESPONSE_SEED, &inbound.guid, &[response_bump]],
+        VerificationResponse::SIZE,
+    )?;
+    // The executor forwards the response fee the querier paid for on EVM
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            system_program.clone(),
+            anchor_lang::system_program::Transfer { from: payer.clone(), to: response_info.clone() },
+        ),
+        query.response_fee,
+    )?;
+
+    let response = VerificationResponse {
+        src_eid: inbound.src_eid,
+        request_guid: inbound.guid,
+        evm_address: query.evm_address,
+        solana_address: query.solana_address,
+        verification,
+        response_fee: query.response_fee,
+        payer: payer.key(),
+        bump: response_bump,
+    };
+    response.try_serialize(&mut &mut response_info.try_borrow_mut_data()?[..])?;
+
+    msg!("Answered verification query for {}: {:?}", query.solana_address, verification);
+    Ok(())
+}
+
+// Queue every link of a LinkBatch. The identity account of entry `i` is the
+// `i`th message account. A batch only proves control of the EVM address, so
+// new links wait in the identity's pending links until its authority accepts
+// them with `review_pending_link`. Entries are applied independently: each
+// outcome is reported in a LinkBatchEntryProcessed event and a failing entry
+// doesn't reject the others. Accounts that don't match the entries do reject
+// the whole message, so the executor can retry it with the right ones.
+fn process_link_batch(identity_infos: &[AccountInfo], inbound: &InboundMessage) -> Result<()> {
+    let batch = LinkBatch::decode(inbound.message)?;
+    if identity_infos.len() < batch.entries.len() {
+        return Err(error!(MyOAppError::InvalidMessageAccounts));
+    }
+
+    for (index, (entry, identity_info)) in batch.entries.iter().zip(identity_infos).enumerate() {
+        let result = apply_batch_entry(entry, identity_info)?;
+        emit!(LinkBatchEntryProcessed {
+            guid: inbound.guid,
+            index: index as u8,
+            evm_address: entry.evm_address,
+            solana_address: entry.solana_address.clone(),
+            result,
+        });
+    }
+    Ok(())
+}
+
+fn apply_batch_entry(entry: &LinkBatchEntry, identity_info: &AccountInfo) -> Result<LinkBatchResult> {
+    let Some(solana_pubkey) = entry.solana_pubkey() else {
+        return Ok(LinkBatchResult::InvalidSolanaAddress);
+    };
+    let (identity_key, _) =
+        Pubkey::find_program_address(&[IDENTITY_SEED, solana_pubkey.as_ref()], &crate::ID);
+    if identity_info.key() != identity_key {
+        return Err(error!(MyOAppError::InvalidMessageAccounts));
+    }
+    // Links are only requested for identities their owner created
+    if identity_info.owner != &crate::ID || identity_info.data_is_empty() {
+        return Ok(LinkBatchResult::IdentityMissing);
+    }
+
+    let mut identity_account =
+        IdentityAccount::try_deserialize(&mut &identity_info.try_borrow_data()?[..])?;
+    if identity_account.frozen {
+        return Ok(LinkBatchResult::IdentityFrozen);
+    }
+    let now = Clock::get()?.unix_timestamp;
+    // Expired links make room and can be linked again
+    identity_account.prune_expired(now);
+    let link = LinkedAddress::new(ChainAddress::Evm(entry.evm_address), None);
+    let existing =
+        identity_account.linked_addresses.iter().position(|linked| linked.subsumes(&link));
+    let result = match existing {
+        // Only a primary request changes anything for an address already linked.
+        // A verified link can take it as is; an unverified one goes through the
+        // authority like a new link, so the EVM side can't pick it on its own.
+        Some(index)
+            if entry.primary
+                && identity_account.linked_addresses[index].verification.is_verified() =>
+        {
+            identity_account.set_primary(index)?;
+            LinkBatchResult::AlreadyLinked
+        },
+        Some(_) if !entry.primary => return Ok(LinkBatchResult::AlreadyLinked),
+        _ if identity_account
+            .pending_links
+            .iter()
+            .any(|pending| pending.evm_address == entry.evm_address) =>
+        {
+            return Ok(LinkBatchResult::AlreadyPending);
+        },
+        // The bound on pending links is what limits the requests an identity
+        // takes in until its authority reviews them
+        _ if identity_account.pending_links.len() >= IdentityAccount::MAX_PENDING_LINKS => {
+            return Ok(LinkBatchResult::TooManyPendingLinks);
+        },
+        _ => {
+            identity_account.pending_links.push(PendingLink {
+                evm_address: entry.evm_address,
+                primary: entry.primary,
+                received_at: now,
+            });
+            LinkBatchResult::Pending
+        },
+    };
+    // Accounts compacted by `prune_expired` may have no room left, and the
+    // message carries no payer to grow them
+    if 8 + identity_account.try_to_vec()?.len() > identity_info.data_len() {
+        return Ok(LinkBatchResult::IdentityTooSmall);
+    }
+    identity_account.try_serialize(&mut &mut identity_info.try_borrow_mut_data()?[..])?;
+    Ok(result)
+}
+
+// Store a chunk in the ReassemblyBuffer of its message, creating the buffer
+// with the first chunk to arrive. The message accounts are: the executor as
+// payer, the ReassemblyBuffer PDA and the system program.
+fn process_chunk(ctx: &Context<LzReceive>, params: &LzReceiveParams) -> Result<()> {
+    let chunk = Chunk::decode(&params.message)?;
+    let accounts = &ctx.remaining_accounts[Clear::MIN_ACCOUNTS_LEN..];
+    let [payer, buffer_info, system_program] = accounts else {
+        return Err(error!(MyOAppError::InvalidMessageAccounts));
+    };
+    let (buffer_key, bump) = Pubkey::find_program_address(
+        &[REASSEMBLY_SEED, &params.src_eid.to_be_bytes(), &chunk.message_id],
+        &crate::ID,
+    );
+    if !payer.is_signer || buffer_info.key() != buffer_key || system_program.key() != System::id() {
+        return Err(error!(MyOAppError::InvalidMessageAccounts));
+    }
+
+    let mut buffer = if buffer_info.owner == &crate::ID && !buffer_info.data_is_empty() {
+        ReassemblyBuffer::try_deserialize(&mut &buffer_info.try_borrow_data()?[..])?
+    } else {
+        pda::create_pda(
+            payer.clone(),
+            buffer_info.clone(),
+            system_program.clone(),
+            &[REASSEMBLY_SEED, &params.src_eid.to_be_bytes(), &chunk.message_id, &[bump]],
+            ReassemblyBuffer::space(chunk.total),
+        )?;
+        ReassemblyBuffer {
+            src_eid: params.src_eid,
+            message_id: chunk.message_id,
+            payer: payer.key(),
+            created_at: Clock::get()?.unix_timestamp,
+            total_chunks: chunk.total,
+            received: 0,
+            payload_len: 0,
+            payload: vec![0; chunk.total as usize * identity_core::CHUNK_DATA_SIZE],
+            bump,
+        }
+    };
+    if buffer.total_chunks != chunk.total || buffer.has_chunk(chunk.index) {
+        return Err(error!(MyOAppError::ChunkMismatch));
+    }
+
+    buffer.write_chunk(chunk.index, &chunk.data);
+    buffer.try_serialize(&mut &mut buffer_info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "Stored chunk {} of {} for message {:?}",
+        chunk.index + 1,
+        chunk.total,
+        chunk.message_id
+    );
+    if buffer.is_complete() {
+        msg!("Message {:?} is ready to be processed", chunk.message_id);
+    }
+    Ok(())
+}
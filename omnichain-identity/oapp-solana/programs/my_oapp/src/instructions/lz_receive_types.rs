@@ -1,5 +1,6 @@
 use crate::*;
-use oapp::endpoint_cpi::{get_accounts_for_clear, LzAccount};
+use identity_core::{Chunk, LinkBatch, LinksResponse, MessageType, VerifyQuery};
+use oapp::endpoint_cpi::{get_accounts_for_clear, get_accounts_for_send_compose, LzAccount};
 use oapp::{endpoint::ID as ENDPOINT_ID, LzReceiveParams};
 
 /// `lz_receive_types` is queried off-chain by the Executor before calling
@@ -47,6 +48,77 @@ impl LzReceiveTypes<'_> {
         );
         accounts.extend(accounts_for_clear);
 
+        // Typed messages need a few more accounts after the ones for clear
+        let message_type = identity_core::message_type(&params.message);
+        if message_type == Some(MessageType::LinksResponse) {
+            let response = LinksResponse::decode(&params.message)?;
+            let (pending_query, _) = Pubkey::find_program_address(
+                &[PENDING_QUERY_SEED, &response.request_guid],
+                ctx.program_id,
+            );
+            accounts.push(LzAccount { pubkey: pending_query, is_signer: false, is_writable: true });
+            // The consumer is notified with the guid of its query
+            if response.compose_to != Pubkey::default() {
+                accounts.extend(get_accounts_for_send_compose(
+                    ENDPOINT_ID,
+                    &store,
+                    &response.compose_to,
+                    &params.guid,
+                    0,
+                    &response.request_guid,
+                ));
+            }
+        }
+        if message_type == Some(MessageType::VerifyQuery) {
+            let query = VerifyQuery::decode(&params.message)?;
+            let (identity_account, _) = Pubkey::find_program_address(
+                &[IDENTITY_SEED, query.solana_address.as_ref()],
+                ctx.program_id,
+            );
+            let (verification_response, _) = Pubkey::find_program_address(
+                &[VERIFICATION_RESPONSE_SEED, &params.guid],
+                ctx.program_id,
+            );
+            accounts.extend([
+                // payer, replaced by the executor
+                LzAccount { pubkey: Pubkey::default(), is_signer: true, is_writable: true },
+                LzAccount { pubkey: identity_account, is_signer: false, is_writable: false },
+                LzAccount { pubkey: verification_response, is_signer: false, is_writable: true },
+                LzAccount { pubkey: System::id(), is_signer: false, is_writable: false },
+            ]);
+        }
+        if message_type == Some(MessageType::Chunk) {
+            let chunk = Chunk::decode(&params.message)?;
+            let (buffer, _) = Pubkey::find_program_address(
+                &[REASSEMBLY_SEED, &params.src_eid.to_be_bytes(), &chunk.message_id],
+                ctx.program_id,
+            );
+            accounts.extend([
+                // payer, replaced by the executor
+                LzAccount { pubkey: Pubkey::default(), is_signer: true, is_writable: true },
+                LzAccount { pubkey: buffer, is_signer: false, is_writable: true },
+                LzAccount { pubkey: System::id(), is_signer: false, is_writable: false },
+            ]);
+        }
+        if message_type == Some(MessageType::LinkBatch) {
+            // One identity account per entry, in order. Entries with an invalid
+            // Solana address get a read-only placeholder to keep the positions.
+            let batch = LinkBatch::decode(&params.message)?;
+            for entry in batch.entries.iter() {
+                let account = match entry.solana_pubkey() {
+                    Some(solana_pubkey) => {
+                        let (identity_account, _) = Pubkey::find_program_address(
+                            &[IDENTITY_SEED, solana_pubkey.as_ref()],
+                            ctx.program_id,
+                        );
+                        LzAccount { pubkey: identity_account, is_signer: false, is_writable: true }
+                    },
+                    None => LzAccount { pubkey: Pubkey::default(), is_signer: false, is_writable: false },
+                };
+                accounts.push(account);
+            }
+        }
+
         Ok(accounts)
     }
 }
@@ -0,0 +1,106 @@
+use crate::*;
+use anchor_lang::{prelude::*, Discriminator};
+use identity_core::{AddressNamespace, ChainAddress, LinkedAddress};
+
+// Rewrites an identity stored in the original layout, where linked addresses
+// were plain "0x" strings, into the current one and grows it to
+// `IdentityAccount::SIZE`. The strings become chain agnostic, self asserted
+// EVM links; strings that don't parse as EVM addresses are dropped. Anyone
+// can migrate an identity, the payer covers the extra rent.
+
+#[derive(Accounts)]
+#[instruction(params: MigrateIdentityParams)]
+pub struct MigrateIdentity<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: read as the original layout below, rejected if it isn't one
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [IDENTITY_SEED, params.authority.as_ref()],
+        bump
+    )]
+    pub identity_account: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl MigrateIdentity<'_> {
+    pub fn apply(ctx: &mut Context<MigrateIdentity>, params: &MigrateIdentityParams) -> Result<()> {
+        let identity_info = ctx.accounts.identity_account.to_account_info();
+        let legacy = LegacyIdentityAccount::load(&identity_info)?;
+        require_keys_eq!(legacy.authority, params.authority, MyOAppError::IdentityNotLegacy);
+
+        let mut linked_addresses: Vec<LinkedAddress> = Vec::new();
+        for address in &legacy.linked_addresses {
+            let Ok(address) = ChainAddress::parse(AddressNamespace::Evm, address) else {
+                continue;
+            };
+            let link = LinkedAddress::new(address, None);
+            if !linked_addresses.iter().any(|linked| linked.subsumes(&link)) {
+                linked_addresses.push(link);
+            }
+        }
+
+        pda::grow_account(
+            ctx.accounts.payer.to_account_info(),
+            identity_info.clone(),
+            ctx.accounts.system_program.to_account_info(),
+            IdentityAccount::SIZE,
+        )?;
+        let migrated = linked_addresses.len();
+        let identity = IdentityAccount {
+            authority: legacy.authority,
+            linked_addresses,
+            bump: ctx.bumps.identity_account,
+            primary: None,
+            evm_manager: None,
+            evm_nonce: 0,
+            frozen: false,
+            link_expiries: Vec::new(),
+            attestation_nonce: 0,
+            pending_links: Vec::new(),
+            open_grants: 0,
+        };
+        identity.try_serialize(&mut &mut identity_info.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "Identity of {} migrated, {} of {} addresses kept",
+            params.authority,
+            migrated,
+            legacy.linked_addresses.len()
+        );
+        Ok(())
+    }
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct MigrateIdentityParams {
+    pub authority: Pubkey, // Wallet the identity belongs to
+}
+
+// `IdentityAccount` as first deployed. It shares the discriminator of the
+// current layout, as the account name didn't change.
+#[derive(AnchorDeserialize)]
+struct LegacyIdentityAccount {
+    authority: Pubkey,
+    linked_addresses: Vec<String>,
+    #[allow(dead_code)]
+    bump: u8,
+}
+
+impl LegacyIdentityAccount {
+    // Identities that already read as the current layout aren't legacy. An
+    // original identity without addresses does, and needs no migration.
+    fn load(info: &AccountInfo) -> Result<Self> {
+        let data = info.try_borrow_data()?;
+        if data.len() < 8
+            || data[..8] != IdentityAccount::DISCRIMINATOR
+            || IdentityAccount::try_deserialize(&mut &data[..]).is_ok()
+        {
+            return Err(error!(MyOAppError::IdentityNotLegacy));
+        }
+        Self::deserialize(&mut &data[8..]).map_err(|_| error!(MyOAppError::IdentityNotLegacy))
+    }
+}
@@ -8,6 +8,44 @@ pub mod get_linked_addresses;
 pub mod init_identity;
 pub mod add_linked_address;
 pub mod is_address_linked;
+pub mod set_attestor;
+pub mod attest_link;
+pub mod downgrade_attested_links;
+pub mod post_dao_snapshot;
+pub mod verify_membership;
+pub mod post_link_import;
+pub mod claim_imported_link;
+pub mod query_evm_links;
+pub mod send_verification_response;
+pub mod process_reassembled_message;
+pub mod reclaim_reassembly_buffer;
+pub mod set_sponsorship;
+pub mod fund_treasury;
+pub mod sponsored_send;
+pub mod quote_message;
+pub mod disable_peer;
+pub mod close_peer;
+pub mod remove_linked_address;
+pub mod set_primary_address;
+pub mod resolve;
+pub mod rotate_authority;
+pub mod set_guardians;
+pub mod approve_recovery;
+pub mod cancel_recovery;
+pub mod execute_recovery;
+pub mod set_evm_manager;
+pub mod evm_remove_link;
+pub mod evm_set_frozen;
+pub mod evm_rotate_authority;
+pub mod set_link_expiry;
+pub mod prune_expired;
+pub mod grant_delegation;
+pub mod revoke_delegation;
+pub mod close_identity;
+pub mod reopen_identity;
+pub mod review_pending_link;
+pub mod close_pending_query;
+pub mod migrate_identity;
 
 pub use send::*;
 pub use init_store::*;
@@ -19,3 +57,41 @@ pub use get_linked_addresses::*;
 pub use init_identity::*;
 pub use add_linked_address::*;
 pub use is_address_linked::*;
+pub use set_attestor::*;
+pub use attest_link::*;
+pub use downgrade_attested_links::*;
+pub use post_dao_snapshot::*;
+pub use verify_membership::*;
+pub use post_link_import::*;
+pub use claim_imported_link::*;
+pub use query_evm_links::*;
+pub use send_verification_response::*;
+pub use process_reassembled_message::*;
+pub use reclaim_reassembly_buffer::*;
+pub use set_sponsorship::*;
+pub use fund_treasury::*;
+pub use sponsored_send::*;
+pub use quote_message::*;
+pub use disable_peer::*;
+pub use close_peer::*;
+pub use remove_linked_address::*;
+pub use set_primary_address::*;
+pub use resolve::*;
+pub use rotate_authority::*;
+pub use set_guardians::*;
+pub use approve_recovery::*;
+pub use cancel_recovery::*;
+pub use execute_recovery::*;
+pub use set_evm_manager::*;
+pub use evm_remove_link::*;
+pub use evm_set_frozen::*;
+pub use evm_rotate_authority::*;
+pub use set_link_expiry::*;
+pub use prune_expired::*;
+pub use grant_delegation::*;
+pub use revoke_delegation::*;
+pub use close_identity::*;
+pub use reopen_identity::*;
+pub use review_pending_link::*;
+pub use close_pending_query::*;
+pub use migrate_identity::*;
@@ -0,0 +1,58 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PostDaoSnapshotParams {
+    pub dao: String,
+    pub block_number: u64,
+    pub chain_reference: String, // EIP-155 chain id the snapshot was taken on
+    pub merkle_root: [u8; 32],
+    pub expires_at: i64,
+    pub require_attested: bool,
+}
+
+#[derive(Accounts)]
+#[instruction(params: PostDaoSnapshotParams)]
+pub struct PostDaoSnapshot<'info> {
+    #[account(mut, address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    // Snapshots are immutable: a new root needs a new block number
+    #[account(
+        init,
+        payer = admin,
+        space = DaoSnapshot::SIZE,
+        seeds = [DAO_SNAPSHOT_SEED, params.dao.as_bytes(), &params.block_number.to_le_bytes()],
+        bump,
+    )]
+    pub snapshot: Account<'info, DaoSnapshot>,
+    pub system_program: Program<'info, System>,
+}
+
+impl PostDaoSnapshot<'_> {
+    pub fn apply(ctx: &mut Context<PostDaoSnapshot>, params: &PostDaoSnapshotParams) -> Result<()> {
+        if params.dao.is_empty() || params.dao.len() > DaoSnapshot::MAX_DAO_LENGTH {
+            return Err(error!(MyOAppError::InvalidDaoName));
+        }
+        // A snapshot is taken on one chain, so the chain agnostic reference isn't allowed
+        if !identity_core::is_valid_chain_reference(&params.chain_reference)
+            || params.chain_reference == identity_core::CHAIN_AGNOSTIC_REFERENCE
+        {
+            return Err(error!(MyOAppError::InvalidChainReference));
+        }
+
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.dao = params.dao.clone();
+        snapshot.block_number = params.block_number;
+        snapshot.chain_reference = params.chain_reference.clone();
+        snapshot.merkle_root = params.merkle_root;
+        snapshot.expires_at = params.expires_at;
+        snapshot.require_attested = params.require_attested;
+        snapshot.bump = ctx.bumps.snapshot;
+
+        msg!("Posted snapshot of {} at block {}", params.dao, params.block_number);
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PostLinkImportParams {
+    pub import_id: u32,
+    pub merkle_root: [u8; 32],
+    pub entry_count: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: PostLinkImportParams)]
+pub struct PostLinkImport<'info> {
+    #[account(mut, address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    // Imports are immutable since claims are tracked by entry index
+    #[account(
+        init,
+        payer = admin,
+        space = LinkImport::SIZE,
+        seeds = [LINK_IMPORT_SEED, &params.import_id.to_le_bytes()],
+        bump,
+    )]
+    pub link_import: Account<'info, LinkImport>,
+    pub system_program: Program<'info, System>,
+}
+
+impl PostLinkImport<'_> {
+    pub fn apply(ctx: &mut Context<PostLinkImport>, params: &PostLinkImportParams) -> Result<()> {
+        let link_import = &mut ctx.accounts.link_import;
+        link_import.import_id = params.import_id;
+        link_import.merkle_root = params.merkle_root;
+        link_import.entry_count = params.entry_count;
+        link_import.bump = ctx.bumps.link_import;
+
+        msg!("Posted link import {} with {} entries", params.import_id, params.entry_count);
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Handles a message once all of its chunks arrived, like `lz_receive` would
+// have handled it unchunked. Anyone can run it: the remaining accounts are the
+// ones the reassembled message needs, in the order `lz_receive_types` would
+// list them after the accounts for `Endpoint::clear`, with the caller as payer.
+// The buffer is closed and its rent refunded to whoever paid for it.
+
+#[derive(Accounts)]
+pub struct ProcessReassembledMessage<'info> {
+    #[account(mut, seeds = [STORE_SEED], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    #[account(
+        mut,
+        close = rent_receiver,
+        seeds = [REASSEMBLY_SEED, &buffer.src_eid.to_be_bytes(), &buffer.message_id],
+        bump = buffer.bump
+    )]
+    pub buffer: Account<'info, ReassemblyBuffer>,
+    /// CHECK: receives the rent of the buffer, checked against the payer recorded in it
+    #[account(mut, address = buffer.payer)]
+    pub rent_receiver: UncheckedAccount<'info>,
+}
+
+impl<'info> ProcessReassembledMessage<'info> {
+    pub fn apply(
+        ctx: &mut Context<'_, '_, '_, 'info, ProcessReassembledMessage<'info>>,
+    ) -> Result<()> {
+        let buffer = &ctx.accounts.buffer;
+        if !buffer.is_complete() {
+            return Err(error!(MyOAppError::ReassemblyIncomplete));
+        }
+
+        let inbound = InboundMessage {
+            src_eid: buffer.src_eid,
+            guid: buffer.message_id,
+            message: buffer.reassembled(),
+        };
+        dispatch_message(&mut ctx.accounts.store, ctx.remaining_accounts, &inbound)
+    }
+}
@@ -0,0 +1,57 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Removes the expired links of an identity and shrinks its account to fit what
+// is left. Anyone can run it: the freed rent goes to the authority, minus
+// `IdentityAccount::PRUNE_TIP` for the cranker. Adding a link later grows the
+// account back.
+
+#[derive(Accounts)]
+pub struct PruneExpired<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    /// CHECK: receives the freed rent, checked against the identity's authority
+    #[account(mut, address = identity_account.authority)]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [IDENTITY_SEED, authority.key().as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+}
+
+impl PruneExpired<'_> {
+    pub fn apply(ctx: &mut Context<PruneExpired>) -> Result<()> {
+        let identity_account = &mut ctx.accounts.identity_account;
+        let pruned = identity_account.prune_expired(Clock::get()?.unix_timestamp);
+        if pruned == 0 {
+            return Err(error!(MyOAppError::NothingToPrune));
+        }
+
+        // The account is written back on exit, so it only needs room for what is left
+        let space = 8 + identity_account.try_to_vec()?.len();
+        let identity_info = identity_account.to_account_info();
+        if space < identity_info.data_len() {
+            identity_info.realloc(space, false)?;
+        }
+
+        // The identity is owned by this program, so its lamports can be moved directly
+        let freed = identity_info.lamports().saturating_sub(Rent::get()?.minimum_balance(space));
+        let tip = freed.min(IdentityAccount::PRUNE_TIP);
+        **identity_info.try_borrow_mut_lamports()? -= freed;
+        **ctx.accounts.cranker.try_borrow_mut_lamports()? += tip;
+        **ctx.accounts.authority.try_borrow_mut_lamports()? += freed - tip;
+
+        msg!(
+            "Pruned {} expired links of {}, refunded {} lamports and tipped {}",
+            pruned,
+            ctx.accounts.authority.key(),
+            freed - tip,
+            tip
+        );
+        Ok(())
+    }
+}
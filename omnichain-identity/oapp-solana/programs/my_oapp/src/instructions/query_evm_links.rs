@@ -0,0 +1,111 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use identity_core::LinksQuery;
+use oapp::endpoint::{
+    instructions::SendParams, state::EndpointSettings, ENDPOINT_SEED, ID as ENDPOINT_ID,
+};
+
+// Asks the EVM `OmnichainIdentityLinker` on `dst_eid` which Solana addresses an
+// EVM account has linked there. The answer arrives later through `lz_receive`
+// and fills the PendingQuery PDA created here, keyed by the guid of the query.
+// The options must give the EVM side enough native value to pay for the response.
+// Once the timeout has passed, `close_pending_query` refunds the PDA's rent.
+
+#[derive(Accounts)]
+#[instruction(params: QueryEvmLinksParams)]
+pub struct QueryEvmLinks<'info> {
+    #[account(mut)]
+    pub requester: Signer<'info>,
+    #[account(
+        seeds = [
+            PEER_SEED,
+            &store.key().to_bytes(),
+            &params.dst_eid.to_be_bytes()
+        ],
+        bump = peer.bump,
+        constraint = !peer.disabled @ MyOAppError::PeerDisabled
+    )]
+    pub peer: Account<'info, PeerConfig>,
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    #[account(seeds = [ENDPOINT_SEED], bump = endpoint.bump, seeds::program = ENDPOINT_ID)]
+    pub endpoint: Account<'info, EndpointSettings>,
+    /// CHECK: PendingQuery PDA for the guid the Endpoint assigns to the query.
+    /// It can only be derived once the message is sent, so it is checked and
+    /// created after the send CPI.
+    #[account(mut)]
+    pub pending_query: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> QueryEvmLinks<'info> {
+    pub fn apply(ctx: &mut Context<QueryEvmLinks>, params: &QueryEvmLinksParams) -> Result<()> {
+        if params.timeout <= 0 || params.timeout > PendingQuery::MAX_TIMEOUT {
+            return Err(error!(MyOAppError::InvalidQueryTimeout));
+        }
+        let compose_to = params.compose_to.unwrap_or_default();
+        let outbound =
+            OutboundMessage::LinksQuery(LinksQuery { evm_address: params.evm_address, compose_to });
+        let options =
+            ctx.accounts.peer.combine_options(outbound.message_type(), &params.options)?;
+        ctx.accounts.peer.assert_options(outbound.message_type(), &options)?;
+        let seeds: &[&[u8]] = &[STORE_SEED, &[ctx.accounts.store.bump]];
+
+        let send_params = SendParams {
+            dst_eid: params.dst_eid,
+            receiver: ctx.accounts.peer.peer_address,
+            message: outbound.encode(),
+            options,
+            native_fee: params.native_fee,
+            lz_token_fee: params.lz_token_fee,
+        };
+        let receipt = oapp::endpoint_cpi::send(
+            ENDPOINT_ID,
+            ctx.accounts.store.key(),
+            ctx.remaining_accounts,
+            seeds,
+            send_params,
+        )?;
+
+        let (pending_query_key, bump) =
+            Pubkey::find_program_address(&[PENDING_QUERY_SEED, &receipt.guid], &crate::ID);
+        if ctx.accounts.pending_query.key() != pending_query_key {
+            return Err(error!(MyOAppError::InvalidPendingQuery));
+        }
+        pda::create_pda(
+            ctx.accounts.requester.to_account_info(),
+            ctx.accounts.pending_query.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &[PENDING_QUERY_SEED, &receipt.guid, &[bump]],
+            PendingQuery::SIZE,
+        )?;
+
+        let pending_query = PendingQuery {
+            requester: ctx.accounts.requester.key(),
+            guid: receipt.guid,
+            dst_eid: params.dst_eid,
+            evm_address: params.evm_address,
+            compose_to,
+            deadline: Clock::get()?.unix_timestamp + params.timeout,
+            status: QueryStatus::Pending,
+            solana_addresses: Vec::new(),
+            bump,
+        };
+        let mut data = ctx.accounts.pending_query.try_borrow_mut_data()?;
+        pending_query.try_serialize(&mut &mut data[..])?;
+
+        msg!("Sent links query {:?} to eid {}", receipt.guid, params.dst_eid);
+        Ok(())
+    }
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct QueryEvmLinksParams {
+    pub dst_eid: u32,
+    pub evm_address: [u8; 20],
+    pub compose_to: Option<Pubkey>, // Program to notify through compose on response
+    pub timeout: i64,               // Seconds to wait for the response, up to MAX_TIMEOUT
+    pub options: Vec<u8>,
+    pub native_fee: u64,
+    pub lz_token_fee: u64,
+}
@@ -0,0 +1,103 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use identity_core::{AuthorityRotation, LinksQuery, MessageType, VerifyResponse};
+use oapp::endpoint::{
+    instructions::QuoteParams, state::EndpointSettings, ENDPOINT_SEED, ID as ENDPOINT_ID,
+};
+
+// Quotes any message this OApp sends, encoded exactly like the instruction
+// that sends it, with the peer's enforced options for its type applied.
+
+#[derive(Accounts)]
+#[instruction(params: QuoteMessageParams)]
+pub struct QuoteMessage<'info> {
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    #[account(
+        seeds = [
+            PEER_SEED,
+            store.key().as_ref(),
+            &params.dst_eid.to_be_bytes()
+        ],
+        bump = peer.bump,
+        constraint = !peer.disabled @ MyOAppError::PeerDisabled
+    )]
+    pub peer: Account<'info, PeerConfig>,
+    #[account(seeds = [ENDPOINT_SEED], bump = endpoint.bump, seeds::program = ENDPOINT_ID)]
+    pub endpoint: Account<'info, EndpointSettings>,
+}
+
+impl QuoteMessage<'_> {
+    pub fn apply(
+        ctx: &Context<QuoteMessage>,
+        params: &QuoteMessageParams,
+    ) -> Result<QuoteMessageResult> {
+        let message = params.message.encode();
+        let encoded_size = message.len() as u32;
+        let options = ctx
+            .accounts
+            .peer
+            .combine_options(params.message.message_type(), &params.options)?;
+        ctx.accounts.peer.assert_options(params.message.message_type(), &options)?;
+
+        let quote_params = QuoteParams {
+            sender: ctx.accounts.store.key(),
+            dst_eid: params.dst_eid,
+            receiver: ctx.accounts.peer.peer_address,
+            message,
+            pay_in_lz_token: params.pay_in_lz_token,
+            options,
+        };
+        let fee = oapp::endpoint_cpi::quote(ENDPOINT_ID, ctx.remaining_accounts, quote_params)?;
+        Ok(QuoteMessageResult { fee, encoded_size })
+    }
+}
+
+// Every kind of message this OApp sends, named after the instruction sending it
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub enum OutboundMessage {
+    // `send` and `sponsored_send`
+    String(String),
+    // `query_evm_links`
+    LinksQuery(LinksQuery),
+    // `send_verification_response`
+    VerifyResponse(VerifyResponse),
+    // `rotate_authority`
+    AuthorityRotation(AuthorityRotation),
+}
+
+impl OutboundMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            OutboundMessage::String(message) => msg_codec::encode(message),
+            OutboundMessage::LinksQuery(query) => query.encode(),
+            OutboundMessage::VerifyResponse(response) => response.encode(),
+            OutboundMessage::AuthorityRotation(rotation) => rotation.encode(),
+        }
+    }
+
+    // Type used to pick the peer's enforced options and minimum lzReceive gas,
+    // None for string messages
+    pub fn message_type(&self) -> Option<MessageType> {
+        match self {
+            OutboundMessage::String(_) => None,
+            OutboundMessage::LinksQuery(_) => Some(MessageType::LinksQuery),
+            OutboundMessage::VerifyResponse(_) => Some(MessageType::VerifyResponse),
+            OutboundMessage::AuthorityRotation(_) => Some(MessageType::AuthorityRotation),
+        }
+    }
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct QuoteMessageParams {
+    pub dst_eid: u32,
+    pub message: OutboundMessage,
+    pub options: Vec<u8>,
+    pub pay_in_lz_token: bool,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct QuoteMessageResult {
+    pub fee: MessagingFee,
+    pub encoded_size: u32, // Size of the encoded message, in bytes
+}
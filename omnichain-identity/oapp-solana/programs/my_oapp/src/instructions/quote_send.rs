@@ -16,7 +16,8 @@ pub struct QuoteSend<'info> {
         store.key().as_ref(),
         &params.dst_eid.to_be_bytes()
     ],
-    bump = peer.bump
+    bump = peer.bump,
+    constraint = !peer.disabled @ MyOAppError::PeerDisabled
     )]
     pub peer: Account<'info, PeerConfig>,
     #[account(seeds = [ENDPOINT_SEED], bump = endpoint.bump, seeds::program = ENDPOINT_ID)]
@@ -26,7 +27,11 @@ pub struct QuoteSend<'info> {
 impl<'info> QuoteSend<'info> {
     pub fn apply(ctx: &Context<QuoteSend>, params: &QuoteSendParams) -> Result<MessagingFee> {
         // Encode the payload for quoting
-        let message = msg_codec::encode(&params.message);
+        let outbound = OutboundMessage::String(params.message.clone());
+        let message = outbound.encode();
+        let options =
+            ctx.accounts.peer.combine_options(outbound.message_type(), &params.options)?;
+        ctx.accounts.peer.assert_options(outbound.message_type(), &options)?;
 
         // Ask the Endpoint how much a send would cost
         let quote_params = QuoteParams {
@@ -35,11 +40,7 @@ impl<'info> QuoteSend<'info> {
             receiver: params.receiver,
             message,
             pay_in_lz_token: params.pay_in_lz_token,
-            options: ctx
-                .accounts
-                .peer
-                .enforced_options
-                .combine_options(&None::<Vec<u8>>,&params.options)?,
+            options,
         };
         oapp::endpoint_cpi::quote(ENDPOINT_ID, ctx.remaining_accounts, quote_params)
     }
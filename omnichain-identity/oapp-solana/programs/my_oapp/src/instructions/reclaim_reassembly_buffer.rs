@@ -0,0 +1,35 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Closes a buffer whose message didn't complete in time, refunding its rent to
+// whoever paid for it. Anyone can run it.
+
+#[derive(Accounts)]
+pub struct ReclaimReassemblyBuffer<'info> {
+    #[account(
+        mut,
+        close = rent_receiver,
+        seeds = [REASSEMBLY_SEED, &buffer.src_eid.to_be_bytes(), &buffer.message_id],
+        bump = buffer.bump
+    )]
+    pub buffer: Account<'info, ReassemblyBuffer>,
+    /// CHECK: receives the rent of the buffer, checked against the payer recorded in it
+    #[account(mut, address = buffer.payer)]
+    pub rent_receiver: UncheckedAccount<'info>,
+}
+
+impl ReclaimReassemblyBuffer<'_> {
+    pub fn apply(ctx: &mut Context<ReclaimReassemblyBuffer>) -> Result<()> {
+        let buffer = &ctx.accounts.buffer;
+        if Clock::get()?.unix_timestamp <= buffer.created_at + ReassemblyBuffer::TIMEOUT {
+            return Err(error!(MyOAppError::ReassemblyNotExpired));
+        }
+        msg!(
+            "Reclaimed buffer of message {:?} with {} of {} chunks",
+            buffer.message_id,
+            buffer.received.count_ones(),
+            buffer.total_chunks
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RemoveLinkedAddressParams {
+    pub account_id: String, // CAIP-10 account id, exactly as it is linked
+}
+
+#[derive(Accounts)]
+#[instruction(params: RemoveLinkedAddressParams)]
+pub struct RemoveLinkedAddress<'info> {
+    /// The identity's authority, or a delegate holding `delegation`
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [IDENTITY_SEED, identity_account.authority.as_ref()],
+        bump = identity_account.bump,
+        constraint = !identity_account.frozen @ MyOAppError::IdentityFrozen
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        seeds = [DELEGATION_SEED, identity_account.key().as_ref(), signer.key().as_ref()],
+        bump = delegation.bump
+    )]
+    /// Grant of the delegate, only needed when the signer isn't the authority
+    pub delegation: Option<Account<'info, Delegation>>,
+}
+
+impl RemoveLinkedAddress<'_> {
+    pub fn apply(
+        ctx: &mut Context<RemoveLinkedAddress>,
+        params: &RemoveLinkedAddressParams,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let delegation = ctx.accounts.delegation.as_deref();
+        let identity_account = &mut ctx.accounts.identity_account;
+        identity_account.check_manager(
+            &ctx.accounts.signer.key(),
+            delegation,
+            DelegationScope::Remove,
+            now,
+        )?;
+        let link = identity_core::parse_account_id(&params.account_id)?;
+        let index = identity_account
+            .position_of(&link)
+            .ok_or_else(|| error!(MyOAppError::AddressNotLinked))?;
+        identity_account.remove_link(index);
+
+        msg!("Removed linked address {} from {}", params.account_id, identity_account.authority);
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Creates a new, empty identity for a wallet whose identity was closed,
+// removing the tombstone left by `close_identity` and refunding its rent. The
+// attestation nonce picks up where the closed identity left it.
+
+#[derive(Accounts)]
+pub struct ReopenIdentity<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [TOMBSTONE_SEED, authority.key().as_ref()],
+        bump = tombstone.bump
+    )]
+    pub tombstone: Account<'info, IdentityTombstone>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = IdentityAccount::SIZE,
+        seeds = [IDENTITY_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl ReopenIdentity<'_> {
+    pub fn apply(ctx: &mut Context<ReopenIdentity>) -> Result<()> {
+        let identity_account = &mut ctx.accounts.identity_account;
+        identity_account.authority = ctx.accounts.authority.key();
+        identity_account.bump = ctx.bumps.identity_account;
+        identity_account.attestation_nonce = ctx.accounts.tombstone.attestation_nonce;
+
+        msg!(
+            "Identity of {} reopened, closed since {}",
+            ctx.accounts.authority.key(),
+            ctx.accounts.tombstone.closed_at
+        );
+        Ok(())
+    }
+}
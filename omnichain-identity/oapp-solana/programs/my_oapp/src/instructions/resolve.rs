@@ -0,0 +1,48 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use identity_core::LinkVerification;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ResolveParams {
+    pub solana_address: Pubkey,
+}
+
+// The primary EVM address with how much trust its link carries. Primaries are
+// chosen by the authority and may be self asserted: consumers that need proof
+// of control should check `verification.is_verified()`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ResolvedPrimary {
+    pub address: String, // EIP-55 checksummed
+    pub verification: LinkVerification,
+}
+
+#[derive(Accounts)]
+#[instruction(params: ResolveParams)]
+pub struct Resolve<'info> {
+    #[account(
+        seeds = [IDENTITY_SEED, params.solana_address.as_ref()],
+        bump,
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+}
+
+impl Resolve<'_> {
+    pub fn apply(
+        ctx: &Context<Resolve>,
+        _params: &ResolveParams,
+    ) -> Result<Option<ResolvedPrimary>> {
+        // None when no primary was chosen or the primary link expired
+        let identity_account = &ctx.accounts.identity_account;
+        let now = Clock::get()?.unix_timestamp;
+        let primary_expired = identity_account
+            .primary
+            .is_some_and(|index| !identity_account.is_active(index as usize, now));
+        if primary_expired {
+            return Ok(None);
+        }
+        Ok(identity_account.primary_link().map(|link| ResolvedPrimary {
+            address: link.address.to_string(),
+            verification: link.verification,
+        }))
+    }
+}
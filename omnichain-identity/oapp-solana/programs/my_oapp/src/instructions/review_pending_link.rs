@@ -0,0 +1,93 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use identity_core::{ChainAddress, LinkedAddress};
+
+// Accepts or rejects a link requested from EVM by a LinkBatch message. Accepted
+// links are added like `add_linked_address` adds them, as chain agnostic and
+// self asserted, and become primary when the request asked for it.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ReviewPendingLinkParams {
+    pub evm_address: [u8; 20], // EVM address of the pending link
+    pub accept: bool,          // Add the link, or drop the request
+}
+
+#[derive(Accounts)]
+#[instruction(params: ReviewPendingLinkParams)]
+pub struct ReviewPendingLink<'info> {
+    #[account(mut)]
+    /// The identity's authority, or a delegate holding `delegation`
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [IDENTITY_SEED, identity_account.authority.as_ref()],
+        bump = identity_account.bump,
+        constraint = !identity_account.frozen @ MyOAppError::IdentityFrozen,
+        realloc = IdentityAccount::SIZE,
+        realloc::payer = signer,
+        realloc::zero = false
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        seeds = [DELEGATION_SEED, identity_account.key().as_ref(), signer.key().as_ref()],
+        bump = delegation.bump
+    )]
+    /// Grant of the delegate, only needed when the signer isn't the authority
+    pub delegation: Option<Account<'info, Delegation>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl ReviewPendingLink<'_> {
+    pub fn apply(
+        ctx: &mut Context<ReviewPendingLink>,
+        params: &ReviewPendingLinkParams,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let delegation = ctx.accounts.delegation.as_deref();
+        let identity_account = &mut ctx.accounts.identity_account;
+        identity_account.check_manager(
+            &ctx.accounts.signer.key(),
+            delegation,
+            DelegationScope::Add,
+            now,
+        )?;
+        let position = identity_account
+            .pending_links
+            .iter()
+            .position(|pending| pending.evm_address == params.evm_address)
+            .ok_or_else(|| error!(MyOAppError::LinkNotPending))?;
+        let pending = identity_account.pending_links.remove(position);
+        if !params.accept {
+            msg!("Rejected pending link for {}", identity_account.authority);
+            return Ok(());
+        }
+
+        // Expired links make room and can be linked again
+        identity_account.prune_expired(now);
+        let link = LinkedAddress::new(ChainAddress::Evm(pending.evm_address), None);
+        let index = match identity_account
+            .linked_addresses
+            .iter()
+            .position(|linked| linked.subsumes(&link))
+        {
+            Some(index) => index,
+            None => {
+                if identity_account.linked_addresses.len() >= IdentityAccount::MAX_ADDRESSES {
+                    msg!("Maximum number of linked addresses reached");
+                    return Err(error!(IdentityError::TooManyLinkedAddresses));
+                }
+                identity_account.linked_addresses.push(link);
+                identity_account.linked_addresses.len() - 1
+            },
+        };
+        if pending.primary {
+            identity_account.set_primary(index)?;
+        }
+
+        msg!("Accepted pending link for {}", identity_account.authority);
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Removes a delegate's grant, refunding its rent to the authority. It works
+// while the identity is frozen, as it only takes rights away.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RevokeDelegationParams {
+    pub delegate: Pubkey,
+}
+
+#[derive(Accounts)]
+#[instruction(params: RevokeDelegationParams)]
+pub struct RevokeDelegation<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [IDENTITY_SEED, authority.key().as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.authority == authority.key() @ IdentityError::InvalidAddress
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [DELEGATION_SEED, identity_account.key().as_ref(), params.delegate.as_ref()],
+        bump = delegation.bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+}
+
+impl RevokeDelegation<'_> {
+    pub fn apply(
+        ctx: &mut Context<RevokeDelegation>,
+        params: &RevokeDelegationParams,
+    ) -> Result<()> {
+        ctx.accounts.identity_account.close_grant();
+        msg!("{} revoked the delegation to {}", ctx.accounts.authority.key(), params.delegate);
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use identity_core::{AuthorityRotation, ChainAddress};
+use oapp::endpoint::{instructions::SendParams, ID as ENDPOINT_ID};
+
+// Moves an identity to a new Solana wallet. Both wallets sign: the links move
+// to a fresh identity PDA under the new key and the old PDA is closed, its rent
+// going back to the old wallet, along with its recovery config if it had one.
+// A tombstone is left for the old wallet, paid out of that refund, like in
+// `close_identity`. Delegations must be revoked first. The new wallet must not
+// have an identity yet; the tombstone of an identity it closed is removed.
+//
+// The old wallet's DAO memberships move along with the links. They go first in
+// the remaining accounts, `params.memberships` of them. Sponsorship records
+// stay with the old wallet.
+//
+// Linked EVM addresses know the identity by its Solana address, so an EVM peer
+// can be told about the rotation in the same instruction. The Endpoint send
+// accounts then go in the remaining accounts after the memberships, like for
+// `send`.
+
+#[derive(Accounts)]
+#[instruction(params: RotateAuthorityParams)]
+pub struct RotateAuthority<'info> {
+    #[account(mut)]
+    pub old_authority: Signer<'info>,
+    #[account(mut)]
+    pub new_authority: Signer<'info>,
+    #[account(
+        mut,
+        close = old_authority,
+        seeds = [IDENTITY_SEED, old_authority.key().as_ref()],
+        bump = old_identity.bump,
+        constraint = old_identity.authority == old_authority.key() @ IdentityError::InvalidAddress,
+        constraint = !old_identity.frozen @ MyOAppError::IdentityFrozen
+    )]
+    pub old_identity: Account<'info, IdentityAccount>,
+    #[account(
+        init,
+        payer = new_authority,
+        space = IdentityAccount::SIZE,
+        seeds = [IDENTITY_SEED, new_authority.key().as_ref()],
+        bump
+    )]
+    pub new_identity: Account<'info, IdentityAccount>,
+    #[account(
+        init_if_needed,
+        payer = old_authority,
+        space = IdentityTombstone::SIZE,
+        seeds = [TOMBSTONE_SEED, old_authority.key().as_ref()],
+        bump
+    )]
+    pub old_tombstone: Account<'info, IdentityTombstone>,
+    /// CHECK: tombstone of the new wallet, removed if it exists, see
+    /// `IdentityTombstone::clear_if_exists`
+    #[account(mut, seeds = [TOMBSTONE_SEED, new_authority.key().as_ref()], bump)]
+    pub new_tombstone: UncheckedAccount<'info>,
+    /// CHECK: recovery config of the old identity, closed if it exists, see
+    /// `RecoveryConfig::close_if_exists`
+    #[account(mut, seeds = [RECOVERY_SEED, old_identity.key().as_ref()], bump)]
+    pub old_recovery: UncheckedAccount<'info>,
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    /// OApp Store PDA that signs the notification
+    pub store: Account<'info, Store>,
+    /// Peer to notify, only needed with `params.notify`
+    pub peer: Option<Account<'info, PeerConfig>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RotateAuthority<'info> {
+    pub fn apply(
+        ctx: &mut Context<'_, '_, 'info, 'info, RotateAuthority<'info>>,
+        params: &RotateAuthorityParams,
+    ) -> Result<()> {
+        let old_authority = ctx.accounts.old_authority.key();
+        let new_authority = ctx.accounts.new_authority.key();
+        if params.memberships as usize > ctx.remaining_accounts.len() {
+            return Err(error!(MyOAppError::InvalidCleanupAccount));
+        }
+        let (memberships, send_accounts) =
+            ctx.remaining_accounts.split_at(params.memberships as usize);
+        for info in memberships {
+            if !Membership::move_if_of(info, &old_authority, &new_authority)? {
+                return Err(error!(MyOAppError::InvalidCleanupAccount));
+            }
+        }
+
+        let old_identity = &mut ctx.accounts.old_identity;
+        if RecoveryConfig::close_if_exists(
+            &ctx.accounts.old_recovery.to_account_info(),
+            &ctx.accounts.old_authority.to_account_info(),
+        )? {
+            old_identity.close_grant();
+        }
+        old_identity.assert_no_open_grants()?;
+        ctx.accounts.old_tombstone.record(
+            old_authority,
+            old_identity.attestation_nonce,
+            ctx.bumps.old_tombstone,
+        )?;
+
+        let cleared_tombstone = IdentityTombstone::clear_if_exists(
+            &ctx.accounts.new_tombstone.to_account_info(),
+            &ctx.accounts.new_authority.to_account_info(),
+        )?;
+        let moved = old_identity.moved_to(new_authority, ctx.bumps.new_identity, cleared_tombstone);
+        let new_identity = &mut ctx.accounts.new_identity;
+        new_identity.set_inner(moved);
+
+        if let Some(notify) = &params.notify {
+            let peer =
+                ctx.accounts.peer.as_ref().ok_or_else(|| error!(MyOAppError::InvalidPeer))?;
+            let (peer_key, _) = Pubkey::find_program_address(
+                &[PEER_SEED, &ctx.accounts.store.key().to_bytes(), &notify.dst_eid.to_be_bytes()],
+                &crate::ID,
+            );
+            if peer.key() != peer_key || peer.disabled {
+                return Err(error!(MyOAppError::InvalidPeer));
+            }
+
+            let evm_addresses = new_identity
+                .linked_addresses
+                .iter()
+                .filter_map(|link| match link.address {
+                    ChainAddress::Evm(evm_address) => Some(evm_address),
+                    _ => None,
+                })
+                .collect();
+            let outbound = OutboundMessage::AuthorityRotation(AuthorityRotation {
+                old_authority,
+                new_authority,
+                evm_addresses,
+            });
+            let options = peer.combine_options(outbound.message_type(), &notify.options)?;
+            peer.assert_options(outbound.message_type(), &options)?;
+
+            let seeds: &[&[u8]] = &[STORE_SEED, &[ctx.accounts.store.bump]];
+            oapp::endpoint_cpi::send(
+                ENDPOINT_ID,
+                ctx.accounts.store.key(),
+                send_accounts,
+                seeds,
+                SendParams {
+                    dst_eid: notify.dst_eid,
+                    receiver: peer.peer_address,
+                    message: outbound.encode(),
+                    options,
+                    native_fee: notify.native_fee,
+                    lz_token_fee: notify.lz_token_fee,
+                },
+            )?;
+        }
+
+        emit!(AuthorityRotated {
+            old_authority,
+            new_authority,
+            linked_addresses: new_identity.linked_addresses.len() as u8,
+            notified_eid: params.notify.as_ref().map(|notify| notify.dst_eid),
+        });
+        Ok(())
+    }
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct RotateAuthorityParams {
+    pub notify: Option<RotationNotifyParams>,
+    pub memberships: u8, // DAO memberships leading the remaining accounts
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct RotationNotifyParams {
+    pub dst_eid: u32,
+    pub options: Vec<u8>,
+    pub native_fee: u64,
+    pub lz_token_fee: u64,
+}
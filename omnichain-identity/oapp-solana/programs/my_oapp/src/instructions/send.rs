@@ -13,7 +13,8 @@ pub struct Send<'info> {
             &store.key().to_bytes(),
             &params.dst_eid.to_be_bytes()
         ],
-        bump = peer.bump
+        bump = peer.bump,
+        constraint = !peer.disabled @ MyOAppError::PeerDisabled
     )]
     /// Configuration for the destination chain. Holds the peer address and any
     /// enforced messaging options.
@@ -27,7 +28,11 @@ pub struct Send<'info> {
 impl<'info> Send<'info> {
     pub fn apply(ctx: &mut Context<Send>, params: &SendMessageParams) -> Result<()> {
         // Serialize the message according to our codec
-        let message = msg_codec::encode(&params.message);
+        let outbound = OutboundMessage::String(params.message.clone());
+        let message = outbound.encode();
+        let options =
+            ctx.accounts.peer.combine_options(outbound.message_type(), &params.options)?;
+        ctx.accounts.peer.assert_options(outbound.message_type(), &options)?;
         // Prepare the seeds for the OApp Store PDA, which is used to sign the CPI call to the Endpoint program.
         let seeds: &[&[u8]] = &[STORE_SEED, &[ctx.accounts.store.bump]];
 
@@ -36,11 +41,7 @@ impl<'info> Send<'info> {
             dst_eid: params.dst_eid,
             receiver: ctx.accounts.peer.peer_address,
             message,
-            options: ctx
-                .accounts
-                .peer
-                .enforced_options
-                .combine_options( &None::<Vec<u8>>, &params.options)?,
+            options,
             native_fee: params.native_fee,
             lz_token_fee: params.lz_token_fee,
         };
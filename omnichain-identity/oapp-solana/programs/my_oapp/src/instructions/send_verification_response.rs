@@ -0,0 +1,85 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use oapp::endpoint::{
+    instructions::SendParams, state::EndpointSettings, ENDPOINT_SEED, ID as ENDPOINT_ID,
+};
+
+// Sends the answer to a verification query back to the EVM chain that asked.
+// Anyone can send it: the response fee delivered with the query goes to the
+// sender, towards the messaging fee. The VerificationResponse PDA is then
+// closed to the executor recorded in it, refunding its rent.
+
+#[derive(Accounts)]
+pub struct SendVerificationResponse<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        close = rent_receiver,
+        seeds = [VERIFICATION_RESPONSE_SEED, &verification_response.request_guid],
+        bump = verification_response.bump
+    )]
+    pub verification_response: Account<'info, VerificationResponse>,
+    /// CHECK: receives the rent, checked against the recorded payer
+    #[account(mut, address = verification_response.payer)]
+    pub rent_receiver: UncheckedAccount<'info>,
+    #[account(
+        seeds = [
+            PEER_SEED,
+            &store.key().to_bytes(),
+            &verification_response.src_eid.to_be_bytes()
+        ],
+        bump = peer.bump,
+        constraint = !peer.disabled @ MyOAppError::PeerDisabled
+    )]
+    pub peer: Account<'info, PeerConfig>,
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    #[account(seeds = [ENDPOINT_SEED], bump = endpoint.bump, seeds::program = ENDPOINT_ID)]
+    pub endpoint: Account<'info, EndpointSettings>,
+}
+
+impl SendVerificationResponse<'_> {
+    pub fn apply(
+        ctx: &mut Context<SendVerificationResponse>,
+        params: &SendVerificationResponseParams,
+    ) -> Result<()> {
+        let response = &ctx.accounts.verification_response;
+        let outbound = OutboundMessage::VerifyResponse(response.to_verify_response());
+        let options =
+            ctx.accounts.peer.combine_options(outbound.message_type(), &params.options)?;
+        ctx.accounts.peer.assert_options(outbound.message_type(), &options)?;
+        let seeds: &[&[u8]] = &[STORE_SEED, &[ctx.accounts.store.bump]];
+
+        // The sender pays the messaging fee, funded by the inbound response fee
+        let response_info = ctx.accounts.verification_response.to_account_info();
+        **response_info.try_borrow_mut_lamports()? -= response.response_fee;
+        **ctx.accounts.payer.try_borrow_mut_lamports()? += response.response_fee;
+
+        let send_params = SendParams {
+            dst_eid: response.src_eid,
+            receiver: ctx.accounts.peer.peer_address,
+            message: outbound.encode(),
+            options,
+            native_fee: params.native_fee,
+            lz_token_fee: params.lz_token_fee,
+        };
+        oapp::endpoint_cpi::send(
+            ENDPOINT_ID,
+            ctx.accounts.store.key(),
+            ctx.remaining_accounts,
+            seeds,
+            send_params,
+        )?;
+
+        msg!("Sent verification response to eid {}", response.src_eid);
+        Ok(())
+    }
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SendVerificationResponseParams {
+    pub options: Vec<u8>,
+    pub native_fee: u64,
+    pub lz_token_fee: u64,
+}
@@ -0,0 +1,86 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Attestors are trusted off-chain verifiers whose ed25519 signatures can add
+// attested links to identities (see `attest_link`). They are kept on the Store
+// and managed by the admin. Revoked attestors keep their slot until pruned.
+
+#[derive(Accounts)]
+pub struct SetAttestor<'info> {
+    #[account(mut, address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [STORE_SEED],
+        bump = store.bump,
+        realloc = Store::SIZE,
+        realloc::payer = admin,
+        realloc::zero = false
+    )]
+    /// Store PDA of this OApp. Grown to the current `Store::SIZE` if it was
+    /// created before the attestor registry existed.
+    pub store: Account<'info, Store>,
+    pub system_program: Program<'info, System>,
+}
+
+impl SetAttestor<'_> {
+    pub fn apply(ctx: &mut Context<SetAttestor>, params: &SetAttestorParams) -> Result<()> {
+        let store = &mut ctx.accounts.store;
+        match params.config {
+            AttestorConfigParam::Add(key) => {
+                if store.attestors.len() >= Store::MAX_ATTESTORS {
+                    return Err(error!(MyOAppError::TooManyAttestors));
+                }
+                let id = store.next_attestor_id;
+                store
+                    .attestors
+                    .push(Attestor { id, key, revoked: false, downgrade_links: false });
+                store.next_attestor_id += 1;
+                msg!("Added attestor {} with key {}", id, key);
+            },
+            AttestorConfigParam::Rotate { attestor_id, new_key } => {
+                let attestor = store.attestor_mut(attestor_id)?;
+                if attestor.revoked {
+                    return Err(error!(MyOAppError::AttestorRevoked));
+                }
+                msg!("Rotated attestor {} from {} to {}", attestor_id, attestor.key, new_key);
+                attestor.key = new_key;
+            },
+            AttestorConfigParam::Revoke { attestor_id, downgrade_links } => {
+                // Revoked attestors stay listed so the links they issued can still be traced
+                // back to them. Downgrading is applied by `downgrade_attested_links`.
+                let attestor = store.attestor_mut(attestor_id)?;
+                attestor.revoked = true;
+                attestor.downgrade_links = downgrade_links;
+                msg!("Revoked attestor {} (downgrade links: {})", attestor_id, downgrade_links);
+            },
+            AttestorConfigParam::Prune { attestor_id } => {
+                if !store.attestor(attestor_id)?.revoked {
+                    return Err(error!(MyOAppError::AttestorNotRevoked));
+                }
+                store.attestors.retain(|attestor| attestor.id != attestor_id);
+                msg!("Pruned attestor {}", attestor_id);
+            },
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SetAttestorParams {
+    pub config: AttestorConfigParam,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub enum AttestorConfigParam {
+    /// Register a new attestor key
+    Add(Pubkey),
+    /// Replace an attestor's key, keeping its id and the links it issued
+    Rotate { attestor_id: u32, new_key: Pubkey },
+    /// Stop accepting attestations from an attestor, optionally downgrading its links
+    Revoke { attestor_id: u32, downgrade_links: bool },
+    /// Free the slot of a revoked attestor. Its links still marked attested are
+    /// downgraded by `downgrade_attested_links`, whatever `downgrade_links` was.
+    Prune { attestor_id: u32 },
+}
@@ -0,0 +1,59 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use identity_core::ChainAddress;
+
+// Lets a verified EVM link manage the identity with signed commands, see
+// `identity_core::evm_command`. Only attested or imported links qualify, as a
+// self asserted link says nothing about who holds the EVM key. `None` turns
+// EVM management off.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetEvmManagerParams {
+    pub account_id: Option<String>, // CAIP-10 account id of the EVM link, exactly as it is linked
+}
+
+#[derive(Accounts)]
+#[instruction(params: SetEvmManagerParams)]
+pub struct SetEvmManager<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [IDENTITY_SEED, authority.key().as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.authority == authority.key() @ IdentityError::InvalidAddress,
+        constraint = !identity_account.frozen @ MyOAppError::IdentityFrozen,
+        realloc = IdentityAccount::SIZE,
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl SetEvmManager<'_> {
+    pub fn apply(ctx: &mut Context<SetEvmManager>, params: &SetEvmManagerParams) -> Result<()> {
+        let identity_account = &mut ctx.accounts.identity_account;
+        let Some(account_id) = &params.account_id else {
+            identity_account.evm_manager = None;
+            msg!("EVM manager of {} removed", ctx.accounts.authority.key());
+            return Ok(());
+        };
+
+        let link = identity_core::parse_account_id(account_id)?;
+        let ChainAddress::Evm(evm_address) = link.address else {
+            return Err(error!(IdentityError::UnsupportedNamespace));
+        };
+        match identity_account.verification_of(&link) {
+            Some(verification) if verification.is_verified() => {},
+            Some(_) => return Err(error!(MyOAppError::EvmManagerNotVerified)),
+            None => return Err(error!(MyOAppError::AddressNotLinked)),
+        }
+        identity_account.evm_manager = Some(evm_address);
+
+        msg!("EVM manager of {} set to {}", ctx.accounts.authority.key(), account_id);
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Registers the guardians that can recover an identity, see `RecoveryConfig`.
+// Calling it again replaces the guardians and drops any pending recovery, so a
+// compromised guardian set can be rotated out by the authority. The threshold
+// must be a majority of the guardians, so two recoveries can't both reach it.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetGuardiansParams {
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8, // More than half of the guardians
+    pub timelock: i64, // Seconds, at least `RecoveryConfig::MIN_TIMELOCK`
+}
+
+#[derive(Accounts)]
+#[instruction(params: SetGuardiansParams)]
+pub struct SetGuardians<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [IDENTITY_SEED, authority.key().as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.authority == authority.key() @ IdentityError::InvalidAddress,
+        constraint = !identity_account.frozen @ MyOAppError::IdentityFrozen
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = RecoveryConfig::SIZE,
+        seeds = [RECOVERY_SEED, identity_account.key().as_ref()],
+        bump
+    )]
+    pub recovery: Account<'info, RecoveryConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl SetGuardians<'_> {
+    pub fn apply(ctx: &mut Context<SetGuardians>, params: &SetGuardiansParams) -> Result<()> {
+        let guardians = &params.guardians;
+        let authority = ctx.accounts.authority.key();
+        let has_duplicates = guardians
+            .iter()
+            .enumerate()
+            .any(|(i, guardian)| guardians[..i].contains(guardian));
+        if guardians.is_empty()
+            || guardians.len() > RecoveryConfig::MAX_GUARDIANS
+            || has_duplicates
+            || guardians.contains(&authority)
+            || params.threshold == 0
+            || params.threshold as usize > guardians.len()
+            || params.threshold as usize * 2 <= guardians.len()
+            || params.timelock < RecoveryConfig::MIN_TIMELOCK
+        {
+            return Err(error!(MyOAppError::InvalidGuardians));
+        }
+
+        let recovery = &mut ctx.accounts.recovery;
+        // First guardians of this identity, rather than a replacement
+        if recovery.identity == Pubkey::default() {
+            ctx.accounts.identity_account.open_grant()?;
+        }
+        recovery.identity = ctx.accounts.identity_account.key();
+        recovery.guardians = guardians.clone();
+        recovery.votes = vec![None; guardians.len()];
+        recovery.threshold = params.threshold;
+        recovery.timelock = params.timelock;
+        recovery.pending = None;
+        recovery.bump = ctx.bumps.recovery;
+
+        msg!("{} guardians set for {}, threshold {}", guardians.len(), authority, params.threshold);
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Makes a link time-limited, or permanent again with `None`. Expired links are
+// ignored by the views and the cross-chain verification, and anyone can remove
+// them with `prune_expired`.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetLinkExpiryParams {
+    pub account_id: String, // CAIP-10 account id, exactly as it is linked
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: SetLinkExpiryParams)]
+pub struct SetLinkExpiry<'info> {
+    #[account(mut)]
+    /// The identity's authority, or a delegate holding `delegation`
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [IDENTITY_SEED, identity_account.authority.as_ref()],
+        bump = identity_account.bump,
+        constraint = !identity_account.frozen @ MyOAppError::IdentityFrozen,
+        realloc = IdentityAccount::SIZE,
+        realloc::payer = signer,
+        realloc::zero = false
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        seeds = [DELEGATION_SEED, identity_account.key().as_ref(), signer.key().as_ref()],
+        bump = delegation.bump
+    )]
+    /// Grant of the delegate, only needed when the signer isn't the authority
+    pub delegation: Option<Account<'info, Delegation>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl SetLinkExpiry<'_> {
+    pub fn apply(ctx: &mut Context<SetLinkExpiry>, params: &SetLinkExpiryParams) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let delegation = ctx.accounts.delegation.as_deref();
+        let identity_account = &mut ctx.accounts.identity_account;
+        identity_account.check_manager(
+            &ctx.accounts.signer.key(),
+            delegation,
+            DelegationScope::Remove,
+            now,
+        )?;
+        if params.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(error!(MyOAppError::InvalidLinkExpiry));
+        }
+
+        let link = identity_core::parse_account_id(&params.account_id)?;
+        let index = identity_account
+            .position_of(&link)
+            .filter(|index| identity_account.is_active(*index, now))
+            .ok_or_else(|| error!(MyOAppError::AddressNotLinked))?;
+        identity_account.set_expiry(index, params.expires_at);
+
+        msg!(
+            "Expiry of {} for {} set to {:?}",
+            params.account_id,
+            identity_account.authority,
+            params.expires_at
+        );
+        Ok(())
+    }
+}
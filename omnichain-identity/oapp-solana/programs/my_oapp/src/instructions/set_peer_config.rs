@@ -1,5 +1,6 @@
 use crate::*;
 use anchor_lang::prelude::*;
+use identity_core::{ExecutionOptions, MessageType};
 
 // PeerConfig PDAs are used to store configuration for each remote chain
 // For each remote chain, a PeerConfig PDA is created with the remote EID as part of the seed
@@ -12,16 +13,22 @@ pub struct SetPeerConfig<'info> {
     /// Admin of the OApp store
     pub admin: Signer<'info>,
     #[account(
-        init_if_needed,
-        payer = admin,
-        space = PeerConfig::SIZE,
+        mut,
         seeds = [PEER_SEED, &store.key().to_bytes(), &params.remote_eid.to_be_bytes()],
         bump
     )]
-    /// Peer configuration PDA for a specific remote chain
-    pub peer: Account<'info, PeerConfig>,
-    #[account(seeds = [STORE_SEED], bump = store.bump)]
-    /// Store PDA of this OApp
+    /// CHECK: peer configuration PDA for a specific remote chain, created if it
+    /// doesn't exist yet, see `PeerConfig::load_or_create`
+    pub peer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [STORE_SEED],
+        bump = store.bump,
+        realloc = Store::SIZE,
+        realloc::payer = admin,
+        realloc::zero = false
+    )]
+    /// Store PDA of this OApp, which lists the configured eids
     pub store: Account<'info, Store>,
     pub system_program: Program<'info, System>,
 }
@@ -29,19 +36,64 @@ pub struct SetPeerConfig<'info> {
 impl SetPeerConfig<'_> {
     pub fn apply(ctx: &mut Context<SetPeerConfig>, params: &SetPeerConfigParams) -> Result<()> {
         // Update or create the peer config PDA
+        let peer_info = ctx.accounts.peer.to_account_info();
+        let mut peer = PeerConfig::load_or_create(
+            &ctx.accounts.admin.to_account_info(),
+            &peer_info,
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.store.key(),
+            params.remote_eid,
+            ctx.bumps.peer,
+        )?;
         match params.config.clone() {
             PeerConfigParam::PeerAddress(peer_address) => {
-                ctx.accounts.peer.peer_address = peer_address;
+                peer.peer_address = peer_address;
             },
             PeerConfigParam::EnforcedOptions { send, send_and_call } => {
+                // Enforced options are appended to every send, reject anything the
+                // executor would not understand
                 oapp::options::assert_type_3(&send)?;
-                ctx.accounts.peer.enforced_options.send = send;
+                ExecutionOptions::decode(&send)?;
+                peer.enforced_options.send = send;
                 oapp::options::assert_type_3(&send_and_call)?;
-                ctx.accounts.peer.enforced_options.send_and_call = send_and_call;
+                ExecutionOptions::decode(&send_and_call)?;
+                peer.enforced_options.send_and_call = send_and_call;
+            },
+            PeerConfigParam::TrustedSender { address, sunset } => {
+                peer.set_trusted_sender(address, sunset)?;
+            },
+            PeerConfigParam::RemoveTrustedSender(address) => {
+                peer.trusted_senders.retain(|trusted| trusted.address != address);
+            },
+            PeerConfigParam::MessageTypeOptions(entries) => {
+                for entry in entries {
+                    if MessageType::from_tag(entry.msg_type).is_none() {
+                        return Err(error!(MyOAppError::InvalidMessageType));
+                    }
+                    if entry.options.len() > ENFORCED_OPTIONS_MESSAGE_TYPE_MAX_LEN {
+                        return Err(error!(MyOAppError::EnforcedOptionsTooLong));
+                    }
+                    if !entry.options.is_empty() {
+                        oapp::options::assert_type_3(&entry.options)?;
+                        ExecutionOptions::decode(&entry.options)?;
+                    }
+                    peer.set_message_type_options(entry.msg_type, entry.options);
+                }
+            },
+            PeerConfigParam::MinLzReceiveGas(entries) => {
+                for entry in entries {
+                    if entry.msg_type != 0 && MessageType::from_tag(entry.msg_type).is_none() {
+                        return Err(error!(MyOAppError::InvalidMessageType));
+                    }
+                    peer.set_min_lz_receive_gas(entry.msg_type, entry.gas);
+                }
             },
         }
         // Store the PDA bump for later validation
-        ctx.accounts.peer.bump = ctx.bumps.peer;
+        peer.bump = ctx.bumps.peer;
+        peer.try_serialize(&mut &mut peer_info.try_borrow_mut_data()?[..])?;
+        // Peers configured before the registry existed are added on their next update
+        ctx.accounts.store.register_peer(params.remote_eid)?;
         Ok(())
     }
 }
@@ -56,5 +108,28 @@ pub struct SetPeerConfigParams {
 pub enum PeerConfigParam {
     PeerAddress([u8; 32]),
     /// Optionally enforce specific send options for this peer
-    EnforcedOptions { send: Vec<u8>, send_and_call: Vec<u8> },
-}
\ No newline at end of file
+    EnforcedOptions {
+        send: Vec<u8>,
+        send_and_call: Vec<u8>,
+    },
+    /// Enforce options per message type, like `OAppOptionsType3.setEnforcedOptions`.
+    /// Empty options remove an entry, so that type falls back to `send`
+    MessageTypeOptions(Vec<MessageTypeOptionsParam>),
+    /// Accept messages from another sender on this chain, optionally until a
+    /// sunset timestamp. To migrate, trust the old linker with a sunset and
+    /// point `PeerAddress` at the new one.
+    TrustedSender {
+        address: [u8; 32],
+        sunset: Option<i64>,
+    },
+    RemoveTrustedSender([u8; 32]),
+    /// Reject sends whose options carry less lzReceive gas than the peer needs
+    /// to handle them, per message type (0 for `send`). Zero removes a minimum.
+    MinLzReceiveGas(Vec<MinLzReceiveGas>),
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct MessageTypeOptionsParam {
+    pub msg_type: u8,
+    pub options: Vec<u8>,
+}
@@ -0,0 +1,60 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetPrimaryAddressParams {
+    pub account_id: String, // CAIP-10 account id of an EVM link, exactly as it is linked
+}
+
+#[derive(Accounts)]
+#[instruction(params: SetPrimaryAddressParams)]
+pub struct SetPrimaryAddress<'info> {
+    #[account(mut)]
+    /// The identity's authority, or a delegate holding `delegation`
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [IDENTITY_SEED, identity_account.authority.as_ref()],
+        bump = identity_account.bump,
+        constraint = !identity_account.frozen @ MyOAppError::IdentityFrozen,
+        realloc = IdentityAccount::SIZE,
+        realloc::payer = signer,
+        realloc::zero = false
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        seeds = [DELEGATION_SEED, identity_account.key().as_ref(), signer.key().as_ref()],
+        bump = delegation.bump
+    )]
+    /// Grant of the delegate, only needed when the signer isn't the authority
+    pub delegation: Option<Account<'info, Delegation>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl SetPrimaryAddress<'_> {
+    pub fn apply(
+        ctx: &mut Context<SetPrimaryAddress>,
+        params: &SetPrimaryAddressParams,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let delegation = ctx.accounts.delegation.as_deref();
+        let identity_account = &mut ctx.accounts.identity_account;
+        identity_account.check_manager(
+            &ctx.accounts.signer.key(),
+            delegation,
+            DelegationScope::Add,
+            now,
+        )?;
+        let link = identity_core::parse_account_id(&params.account_id)?;
+        let index = identity_account
+            .position_of(&link)
+            .ok_or_else(|| error!(MyOAppError::AddressNotLinked))?;
+        identity_account.set_primary(index)?;
+
+        msg!("Primary address of {} set to {}", identity_account.authority, params.account_id);
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetSponsorship<'info> {
+    #[account(mut, address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [STORE_SEED],
+        bump = store.bump,
+        realloc = Store::SIZE,
+        realloc::payer = admin,
+        realloc::zero = false
+    )]
+    pub store: Account<'info, Store>,
+    pub system_program: Program<'info, System>,
+}
+
+impl SetSponsorship<'_> {
+    pub fn apply(ctx: &mut Context<SetSponsorship>, params: &SetSponsorshipParams) -> Result<()> {
+        ctx.accounts.store.sponsorship = params.config;
+        msg!(
+            "Sponsorship set: {} sends per user, {} lamports per send, {} lamports in total",
+            params.config.max_sends_per_user,
+            params.config.max_fee_per_send,
+            params.config.spend_cap
+        );
+        Ok(())
+    }
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SetSponsorshipParams {
+    pub config: SponsorshipConfig,
+}
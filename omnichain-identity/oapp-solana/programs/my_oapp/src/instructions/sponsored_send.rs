@@ -0,0 +1,159 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use oapp::endpoint::{
+    instructions::SendParams, state::EndpointSettings, ENDPOINT_SEED, ID as ENDPOINT_ID,
+};
+
+// Sends the identity sync message of the user, the identity's active links as
+// CAIP-10 account ids, with the native fee paid by the treasury within the
+// limits set on the Store. Only identities with at least one verified link are
+// sponsored, so throwaway wallets can't draw on the treasury. The fee is moved
+// to the user right before the Endpoint call, where the user pays it as usual,
+// and what the Endpoint didn't charge goes back to the treasury.
+
+#[derive(Accounts)]
+#[instruction(params: SponsoredSendParams)]
+pub struct SponsoredSend<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [IDENTITY_SEED, user.key().as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.authority == user.key() @ IdentityError::InvalidAddress
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+    #[account(
+        seeds = [
+            PEER_SEED,
+            &store.key().to_bytes(),
+            &params.dst_eid.to_be_bytes()
+        ],
+        bump = peer.bump,
+        constraint = !peer.disabled @ MyOAppError::PeerDisabled
+    )]
+    pub peer: Account<'info, PeerConfig>,
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    #[account(seeds = [ENDPOINT_SEED], bump = endpoint.bump, seeds::program = ENDPOINT_ID)]
+    pub endpoint: Account<'info, EndpointSettings>,
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = SponsorshipRecord::SIZE,
+        seeds = [SPONSORSHIP_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub sponsorship_record: Account<'info, SponsorshipRecord>,
+    pub system_program: Program<'info, System>,
+}
+
+impl SponsoredSend<'_> {
+    pub fn apply(ctx: &mut Context<SponsoredSend>, params: &SponsoredSendParams) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let identity_account = &ctx.accounts.identity_account;
+        if !identity_account.active_links(now).any(|link| link.verification.is_verified()) {
+            return Err(error!(MyOAppError::SponsorshipNotEligible));
+        }
+        let message = sync_message(identity_account, now);
+
+        let config = ctx.accounts.store.sponsorship;
+        let record = &ctx.accounts.sponsorship_record;
+        let treasury = &ctx.accounts.treasury;
+        if params.native_fee > config.max_fee_per_send {
+            return Err(error!(MyOAppError::SponsoredFeeTooHigh));
+        }
+        if record.sends >= config.max_sends_per_user {
+            return Err(error!(MyOAppError::SponsorshipQuotaExceeded));
+        }
+        if treasury.total_spent.saturating_add(params.native_fee) > config.spend_cap {
+            return Err(error!(MyOAppError::SponsorshipCapReached));
+        }
+        // The treasury must stay rent exempt
+        let treasury_info = treasury.to_account_info();
+        let available = treasury_info
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(treasury_info.data_len()));
+        if params.native_fee > available {
+            return Err(error!(MyOAppError::InsufficientTreasuryFunds));
+        }
+
+        // The treasury is owned by this program, so its lamports can be moved directly
+        **treasury_info.try_borrow_mut_lamports()? -= params.native_fee;
+        **ctx.accounts.user.try_borrow_mut_lamports()? += params.native_fee;
+
+        let outbound = OutboundMessage::String(message);
+        let options =
+            ctx.accounts.peer.combine_options(outbound.message_type(), &params.options)?;
+        ctx.accounts.peer.assert_options(outbound.message_type(), &options)?;
+        let seeds: &[&[u8]] = &[STORE_SEED, &[ctx.accounts.store.bump]];
+        let send_params = SendParams {
+            dst_eid: params.dst_eid,
+            receiver: ctx.accounts.peer.peer_address,
+            message: outbound.encode(),
+            options,
+            native_fee: params.native_fee,
+            lz_token_fee: params.lz_token_fee,
+        };
+        let receipt = oapp::endpoint_cpi::send(
+            ENDPOINT_ID,
+            ctx.accounts.store.key(),
+            ctx.remaining_accounts,
+            seeds,
+            send_params,
+        )?;
+
+        // Return what the Endpoint didn't charge
+        let spent = receipt.fee.native_fee.min(params.native_fee);
+        let unspent = params.native_fee - spent;
+        if unspent > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.user.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                ),
+                unspent,
+            )?;
+        }
+
+        let record = &mut ctx.accounts.sponsorship_record;
+        record.user = ctx.accounts.user.key();
+        record.sends += 1;
+        record.spent += spent;
+        record.bump = ctx.bumps.sponsorship_record;
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.total_spent += spent;
+        treasury.sponsored_sends += 1;
+
+        emit!(SponsoredFeePaid {
+            user: record.user,
+            dst_eid: params.dst_eid,
+            guid: receipt.guid,
+            fee: spent,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SponsoredSendParams {
+    pub dst_eid: u32,
+    pub options: Vec<u8>,
+    pub native_fee: u64,
+    pub lz_token_fee: u64,
+}
+
+// The identity sync message: the active links, as `get_linked_addresses`
+// returns them, joined by commas
+pub fn sync_message(identity_account: &IdentityAccount, now: i64) -> String {
+    identity_account
+        .active_links(now)
+        .map(identity_core::format_account_id)
+        .collect::<Vec<_>>()
+        .join(",")
+}
@@ -0,0 +1,114 @@
+use crate::*;
+use anchor_lang::prelude::*;
+use identity_core::{ChainAddress, LinkVerification, LinkedAddress};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VerifyMembershipParams {
+    pub evm_address: [u8; 20], // Linked EVM address holding the weight
+    pub weight: u64,
+    pub proof: Vec<[u8; 32]>, // Sibling hashes from the leaf up to the root
+}
+
+#[derive(Accounts)]
+#[instruction(params: VerifyMembershipParams)]
+pub struct VerifyMembership<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [IDENTITY_SEED, authority.key().as_ref()],
+        bump = identity_account.bump,
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+    pub snapshot: Account<'info, DaoSnapshot>,
+    // One membership per holder: re-verifying overwrites it, and another wallet
+    // only takes it over once the holder's identity lost its link, see `apply`
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = Membership::SIZE,
+        seeds = [MEMBERSHIP_SEED, snapshot.key().as_ref(), &params.evm_address],
+        bump,
+    )]
+    pub membership: Account<'info, Membership>,
+    pub system_program: Program<'info, System>,
+}
+
+impl VerifyMembership<'_> {
+    pub fn apply(
+        ctx: &mut Context<VerifyMembership>,
+        params: &VerifyMembershipParams,
+    ) -> Result<()> {
+        let snapshot = &ctx.accounts.snapshot;
+        let now = Clock::get()?.unix_timestamp;
+        if now > snapshot.expires_at {
+            return Err(error!(MyOAppError::SnapshotExpired));
+        }
+
+        // The address must be linked for the snapshot's chain
+        let query = LinkedAddress::new(
+            ChainAddress::Evm(params.evm_address),
+            Some(snapshot.chain_reference.clone()),
+        );
+        if !holds_weight(&ctx.accounts.identity_account, snapshot, &query, now) {
+            return Err(error!(MyOAppError::AddressNotLinked));
+        }
+
+        // The weight of an address counts for one wallet. The wallet holding it
+        // keeps it while its identity still has a link that counts; the first
+        // remaining account must then be that identity, which may not exist.
+        let holder = ctx.accounts.membership.authority;
+        if holder != Pubkey::default() && holder != ctx.accounts.authority.key() {
+            let holder_identity =
+                ctx.remaining_accounts.first().ok_or(MyOAppError::MembershipClaimed)?;
+            let (holder_identity_key, _) =
+                Pubkey::find_program_address(&[IDENTITY_SEED, holder.as_ref()], &crate::ID);
+            if holder_identity.key() != holder_identity_key {
+                return Err(error!(MyOAppError::MembershipClaimed));
+            }
+            if holder_identity.owner == &crate::ID && !holder_identity.data_is_empty() {
+                let holder_identity =
+                    IdentityAccount::try_deserialize(&mut &holder_identity.try_borrow_data()?[..])?;
+                if holds_weight(&holder_identity, snapshot, &query, now) {
+                    return Err(error!(MyOAppError::MembershipClaimed));
+                }
+            }
+        }
+
+        let leaf = identity_core::membership_leaf(&params.evm_address, params.weight);
+        if !identity_core::verify_merkle_proof(&params.proof, &snapshot.merkle_root, leaf) {
+            return Err(error!(MyOAppError::InvalidMerkleProof));
+        }
+
+        let membership = &mut ctx.accounts.membership;
+        membership.authority = ctx.accounts.authority.key();
+        membership.snapshot = snapshot.key();
+        membership.evm_address = params.evm_address;
+        membership.weight = params.weight;
+        membership.expires_at = snapshot.expires_at;
+        membership.bump = ctx.bumps.membership;
+
+        msg!(
+            "{} is a member of {} with weight {}",
+            membership.authority,
+            snapshot.dao,
+            params.weight
+        );
+        Ok(())
+    }
+}
+
+// Whether `identity` has an active link to `query` that the snapshot counts.
+// Self asserted links never count: anyone can add those.
+fn holds_weight(
+    identity: &IdentityAccount,
+    snapshot: &DaoSnapshot,
+    query: &LinkedAddress,
+    now: i64,
+) -> bool {
+    identity.active_links(now).any(|linked| {
+        linked.covers(query)
+            && linked.verification.is_verified()
+            && (!snapshot.require_attested
+                || matches!(linked.verification, LinkVerification::Attested { .. }))
+    })
+}
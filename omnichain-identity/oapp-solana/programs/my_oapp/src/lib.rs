@@ -1,12 +1,19 @@
 mod errors;
+mod events;
 mod instructions;
 mod msg_codec;
-mod state;
-mod identity_msg_codec;
+mod pda;
+pub mod state;
 
 use anchor_lang::prelude::*;
 use instructions::*;
 use errors::*;
+use events::*;
+use identity_core::{
+    IdentityError, DAO_SNAPSHOT_SEED, DELEGATION_SEED, IDENTITY_SEED, IMPORT_CLAIMS_SEED,
+    LINK_IMPORT_SEED, MEMBERSHIP_SEED, PENDING_QUERY_SEED, REASSEMBLY_SEED, RECOVERY_SEED,
+    SPONSORSHIP_SEED, TOMBSTONE_SEED, TREASURY_SEED, VERIFICATION_RESPONSE_SEED,
+};
 use oapp::{endpoint::MessagingFee, endpoint_cpi::LzAccount, LzReceiveParams};
 use solana_helper::program_id_from_env;
 use state::*;
@@ -23,7 +30,6 @@ declare_id!(anchor_lang::solana_program::pubkey::Pubkey::new_from_array(program_
 const LZ_RECEIVE_TYPES_SEED: &[u8] = b"LzReceiveTypes"; // The Executor relies on this exact seed to derive the LzReceiveTypes PDA. Keep it the same.
 const STORE_SEED: &[u8] = b"Store"; // You are free to edit this seed.
 const PEER_SEED: &[u8] = b"Peer"; // The Executor relies on this exact seed to derive the LzReceiveTypes PDA. Keep it the same.
-const IDENTITY_SEED: &[u8] = b"identity"; // Seed for identity account PDAs
 
 #[program]
 pub mod my_oapp {
@@ -44,19 +50,103 @@ pub mod my_oapp {
         SetPeerConfig::apply(&mut ctx, &params)
     }
 
+    // admin instruction to add, rotate or revoke a trusted link attestor.
+    pub fn set_attestor(mut ctx: Context<SetAttestor>, params: SetAttestorParams) -> Result<()> {
+        SetAttestor::apply(&mut ctx, &params)
+    }
+
+    // admin instruction to post a Merkle snapshot of a DAO's EVM holders.
+    pub fn post_dao_snapshot(
+        mut ctx: Context<PostDaoSnapshot>,
+        params: PostDaoSnapshotParams,
+    ) -> Result<()> {
+        PostDaoSnapshot::apply(&mut ctx, &params)
+    }
+
+    // admin instruction to publish a Merkle root of pre-existing identity links.
+    pub fn post_link_import(
+        mut ctx: Context<PostLinkImport>,
+        params: PostLinkImportParams,
+    ) -> Result<()> {
+        PostLinkImport::apply(&mut ctx, &params)
+    }
+
+    // admin instruction to stop or resume all messaging with a remote chain.
+    pub fn disable_peer(mut ctx: Context<DisablePeer>, params: DisablePeerParams) -> Result<()> {
+        DisablePeer::apply(&mut ctx, &params)
+    }
+
+    // admin instruction to close a peer config and refund its rent.
+    pub fn close_peer(mut ctx: Context<ClosePeer>, params: ClosePeerParams) -> Result<()> {
+        ClosePeer::apply(&mut ctx, &params)
+    }
+
+    // admin instruction to set the limits on fees paid by the treasury.
+    pub fn set_sponsorship(
+        mut ctx: Context<SetSponsorship>,
+        params: SetSponsorshipParams,
+    ) -> Result<()> {
+        SetSponsorship::apply(&mut ctx, &params)
+    }
+
+    // admin instruction to fund the fee sponsorship treasury.
+    pub fn fund_treasury(mut ctx: Context<FundTreasury>, params: FundTreasuryParams) -> Result<()> {
+        FundTreasury::apply(&mut ctx, &params)
+    }
+
     // ============================== Public ==============================
     // public instruction returning the estimated MessagingFee for sending a message.
     pub fn quote_send(ctx: Context<QuoteSend>, params: QuoteSendParams) -> Result<MessagingFee> {
         QuoteSend::apply(&ctx, &params)
     }
 
+    // public instruction returning the estimated MessagingFee and encoded size of any outbound message.
+    pub fn quote_message(
+        ctx: Context<QuoteMessage>,
+        params: QuoteMessageParams,
+    ) -> Result<QuoteMessageResult> {
+        QuoteMessage::apply(&ctx, &params)
+    }
+
     // public instruction to send a message to a cross-chain peer.
     pub fn send(mut ctx: Context<Send>, params: SendMessageParams) -> Result<()> {
         Send::apply(&mut ctx, &params)
     }
 
+    // public instruction to send a verified identity's sync message with the native fee paid by the treasury.
+    pub fn sponsored_send(
+        mut ctx: Context<SponsoredSend>,
+        params: SponsoredSendParams,
+    ) -> Result<()> {
+        SponsoredSend::apply(&mut ctx, &params)
+    }
+
+    // public instruction to ask an EVM peer which Solana addresses an EVM account linked there.
+    pub fn query_evm_links(
+        mut ctx: Context<QueryEvmLinks>,
+        params: QueryEvmLinksParams,
+    ) -> Result<()> {
+        QueryEvmLinks::apply(&mut ctx, &params)
+    }
+
+    // permissionless handler closing a links query past its deadline
+    pub fn close_pending_query(mut ctx: Context<ClosePendingQuery>) -> Result<()> {
+        ClosePendingQuery::apply(&mut ctx)
+    }
+
+    // public instruction to send the answer to a verification query back to the EVM peer that asked.
+    pub fn send_verification_response(
+        mut ctx: Context<SendVerificationResponse>,
+        params: SendVerificationResponseParams,
+    ) -> Result<()> {
+        SendVerificationResponse::apply(&mut ctx, &params)
+    }
+
     // handler for processing incoming cross-chain messages and executing the LzReceive logic
-    pub fn lz_receive(mut ctx: Context<LzReceive>, params: LzReceiveParams) -> Result<()> {
+    pub fn lz_receive<'info>(
+        mut ctx: Context<'_, '_, '_, 'info, LzReceive<'info>>,
+        params: LzReceiveParams,
+    ) -> Result<()> {
         LzReceive::apply(&mut ctx, &params)
     }
 
@@ -67,8 +157,20 @@ pub mod my_oapp {
     ) -> Result<Vec<LzAccount>> {
         LzReceiveTypes::apply(&ctx, &params)
     }
+
+    // permissionless handler for a message whose chunks were all received
+    pub fn process_reassembled_message<'info>(
+        mut ctx: Context<'_, '_, '_, 'info, ProcessReassembledMessage<'info>>,
+    ) -> Result<()> {
+        ProcessReassembledMessage::apply(&mut ctx)
+    }
+
+    // permissionless handler closing a chunked message buffer that timed out
+    pub fn reclaim_reassembly_buffer(mut ctx: Context<ReclaimReassemblyBuffer>) -> Result<()> {
+        ReclaimReassemblyBuffer::apply(&mut ctx)
+    }
     
-    // handler to get linked addresses for a Solana wallet
+    // handler to get linked addresses for a Solana wallet, as CAIP-10 account ids
     pub fn get_linked_addresses(
         ctx: Context<GetLinkedAddresses>,
         params: GetLinkedAddressesParams,
@@ -84,7 +186,7 @@ pub mod my_oapp {
         InitIdentity::apply(&mut ctx, &params)
     }
     
-    // handler to manually add a linked EVM address to the Solana wallet
+    // handler to manually link a CAIP-10 account (EVM, Aptos, Sui, Cosmos or Bitcoin) to the Solana wallet
     pub fn add_linked_address(
         mut ctx: Context<AddLinkedAddress>,
         params: AddLinkedAddressParams,
@@ -92,7 +194,7 @@ pub mod my_oapp {
         AddLinkedAddress::apply(&mut ctx, &params)
     }
     
-    // handler to check if an EVM address is linked to a specific Solana wallet
+    // handler to check if a CAIP-10 account is linked to a specific Solana wallet
     pub fn is_address_linked(
         ctx: Context<IsAddressLinked>,
         params: IsAddressLinkedParams,
@@ -100,4 +202,179 @@ pub mod my_oapp {
         IsAddressLinked::apply(&ctx, &params)
     }
 
+    // handler to remove a linked account from the Solana wallet
+    pub fn remove_linked_address(
+        mut ctx: Context<RemoveLinkedAddress>,
+        params: RemoveLinkedAddressParams,
+    ) -> Result<()> {
+        RemoveLinkedAddress::apply(&mut ctx, &params)
+    }
+
+    // handler to choose which linked EVM address `resolve` returns
+    pub fn set_primary_address(
+        mut ctx: Context<SetPrimaryAddress>,
+        params: SetPrimaryAddressParams,
+    ) -> Result<()> {
+        SetPrimaryAddress::apply(&mut ctx, &params)
+    }
+
+    // handler to move an identity to a new Solana wallet, signed by both wallets
+    pub fn rotate_authority<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, RotateAuthority<'info>>,
+        params: RotateAuthorityParams,
+    ) -> Result<()> {
+        RotateAuthority::apply(&mut ctx, &params)
+    }
+
+    // handler to register the guardians that can recover an identity
+    pub fn set_guardians(mut ctx: Context<SetGuardians>, params: SetGuardiansParams) -> Result<()> {
+        SetGuardians::apply(&mut ctx, &params)
+    }
+
+    // handler for a guardian to approve moving an identity to a new authority
+    pub fn approve_recovery(
+        mut ctx: Context<ApproveRecovery>,
+        params: ApproveRecoveryParams,
+    ) -> Result<()> {
+        ApproveRecovery::apply(&mut ctx, &params)
+    }
+
+    // handler for the authority to cancel a pending recovery
+    pub fn cancel_recovery(mut ctx: Context<CancelRecovery>) -> Result<()> {
+        CancelRecovery::apply(&mut ctx)
+    }
+
+    // handler to complete a recovery once its timelock has passed
+    pub fn execute_recovery<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, ExecuteRecovery<'info>>,
+        params: ExecuteRecoveryParams,
+    ) -> Result<()> {
+        ExecuteRecovery::apply(&mut ctx, &params)
+    }
+
+    // handler to let a verified EVM link manage the identity with signed commands
+    pub fn set_evm_manager(
+        mut ctx: Context<SetEvmManager>,
+        params: SetEvmManagerParams,
+    ) -> Result<()> {
+        SetEvmManager::apply(&mut ctx, &params)
+    }
+
+    // handler to remove a link with a command signed by the EVM manager
+    pub fn evm_remove_link(
+        mut ctx: Context<EvmRemoveLink>,
+        params: EvmRemoveLinkParams,
+    ) -> Result<()> {
+        EvmRemoveLink::apply(&mut ctx, &params)
+    }
+
+    // handler to freeze or unfreeze an identity with a command signed by the EVM manager
+    pub fn evm_set_frozen(
+        mut ctx: Context<EvmSetFrozen>,
+        params: EvmSetFrozenParams,
+    ) -> Result<()> {
+        EvmSetFrozen::apply(&mut ctx, &params)
+    }
+
+    // handler to move an identity to a new wallet with a command signed by the EVM manager
+    pub fn evm_rotate_authority<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, EvmRotateAuthority<'info>>,
+        params: EvmRotateAuthorityParams,
+    ) -> Result<()> {
+        EvmRotateAuthority::apply(&mut ctx, &params)
+    }
+
+    // handler to make a link time-limited, or permanent again
+    pub fn set_link_expiry(
+        mut ctx: Context<SetLinkExpiry>,
+        params: SetLinkExpiryParams,
+    ) -> Result<()> {
+        SetLinkExpiry::apply(&mut ctx, &params)
+    }
+
+    // handler for anyone to remove expired links, refunding their rent to the authority
+    pub fn prune_expired(mut ctx: Context<PruneExpired>) -> Result<()> {
+        PruneExpired::apply(&mut ctx)
+    }
+
+    // handler to let a delegate manage the identity's links within a scope
+    pub fn grant_delegation(
+        mut ctx: Context<GrantDelegation>,
+        params: GrantDelegationParams,
+    ) -> Result<()> {
+        GrantDelegation::apply(&mut ctx, &params)
+    }
+
+    // handler to revoke a delegate's grant
+    pub fn revoke_delegation(
+        mut ctx: Context<RevokeDelegation>,
+        params: RevokeDelegationParams,
+    ) -> Result<()> {
+        RevokeDelegation::apply(&mut ctx, &params)
+    }
+
+    // handler to delete an identity, refunding its rent and leaving a tombstone
+    pub fn close_identity<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, CloseIdentity<'info>>,
+    ) -> Result<()> {
+        CloseIdentity::apply(&mut ctx)
+    }
+
+    // handler to create an identity again after `close_identity`
+    pub fn reopen_identity(mut ctx: Context<ReopenIdentity>) -> Result<()> {
+        ReopenIdentity::apply(&mut ctx)
+    }
+
+    // permissionless handler moving an identity from the original string layout to the current one
+    pub fn migrate_identity(
+        mut ctx: Context<MigrateIdentity>,
+        params: MigrateIdentityParams,
+    ) -> Result<()> {
+        MigrateIdentity::apply(&mut ctx, &params)
+    }
+
+    // handler to accept or reject a link requested from EVM
+    pub fn review_pending_link(
+        mut ctx: Context<ReviewPendingLink>,
+        params: ReviewPendingLinkParams,
+    ) -> Result<()> {
+        ReviewPendingLink::apply(&mut ctx, &params)
+    }
+
+    // handler to get the primary EVM address of a Solana wallet and its verification
+    pub fn resolve(
+        ctx: Context<Resolve>,
+        params: ResolveParams,
+    ) -> Result<Option<ResolvedPrimary>> {
+        Resolve::apply(&ctx, &params)
+    }
+
+    // handler to add a link attested by a trusted attestor's ed25519 signature
+    pub fn attest_link(mut ctx: Context<AttestLink>, params: AttestLinkParams) -> Result<()> {
+        AttestLink::apply(&mut ctx, &params)
+    }
+
+    // permissionless handler that downgrades links issued by revoked attestors
+    pub fn downgrade_attested_links<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DowngradeAttestedLinks<'info>>,
+    ) -> Result<()> {
+        DowngradeAttestedLinks::apply(&ctx)
+    }
+
+    // handler to prove DAO membership through a verified EVM link
+    pub fn verify_membership(
+        mut ctx: Context<VerifyMembership>,
+        params: VerifyMembershipParams,
+    ) -> Result<()> {
+        VerifyMembership::apply(&mut ctx, &params)
+    }
+
+    // handler to claim a link from a bulk import with a Merkle proof
+    pub fn claim_imported_link(
+        mut ctx: Context<ClaimImportedLink>,
+        params: ClaimImportedLinkParams,
+    ) -> Result<()> {
+        ClaimImportedLink::apply(&mut ctx, &params)
+    }
+
 }
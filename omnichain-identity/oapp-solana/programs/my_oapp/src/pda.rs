@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Allocate, Assign, CreateAccount, Transfer};
+
+// Creates a program owned PDA whose address is only known inside the
+// instruction, so it can't go through Anchor's `init` constraint. Follows the
+// same steps as `init`, which also work when the address was funded beforehand.
+pub fn create_pda<'info>(
+    payer: AccountInfo<'info>,
+    account: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+    space: usize,
+) -> Result<()> {
+    let signer_seeds = &[signer_seeds];
+    let rent = Rent::get()?.minimum_balance(space);
+
+    if account.lamports() == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program,
+                CreateAccount { from: payer, to: account },
+                signer_seeds,
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        );
+    }
+
+    let top_up = rent.saturating_sub(account.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(system_program.clone(), Transfer { from: payer, to: account.clone() }),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Allocate { account_to_allocate: account.clone() },
+            signer_seeds,
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program,
+            Assign { account_to_assign: account },
+            signer_seeds,
+        ),
+        &crate::ID,
+    )
+}
+
+// Grows a program owned account to `space`, topping up its rent first. Anchor's
+// `realloc` constraint can't be combined with `init_if_needed`, so accounts
+// that may be created in the same instruction are grown with this instead.
+pub fn grow_account<'info>(
+    payer: AccountInfo<'info>,
+    account: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    space: usize,
+) -> Result<()> {
+    if account.data_len() >= space {
+        return Ok(());
+    }
+    let top_up = Rent::get()?.minimum_balance(space).saturating_sub(account.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(system_program, Transfer { from: payer, to: account.clone() }),
+            top_up,
+        )?;
+    }
+    account.realloc(space, false)?;
+    Ok(())
+}
+
+// Closes a program owned account the way Anchor's `close` constraint does, for
+// accounts that are only known from the remaining accounts
+pub fn close_pda<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let lamports = account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    **destination.try_borrow_mut_lamports()? += lamports;
+    account.assign(&system_program::ID);
+    account.realloc(0, false)?;
+    Ok(())
+}
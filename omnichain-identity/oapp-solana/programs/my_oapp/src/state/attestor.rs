@@ -0,0 +1,17 @@
+use crate::*;
+
+// A trusted off-chain verifier (e.g. a KYC provider or a custodial exchange)
+// allowed to attest links. The id stays the same across key rotations, so links
+// keep pointing at the same attestor.
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct Attestor {
+    pub id: u32,
+    pub key: Pubkey,
+    pub revoked: bool,
+    // When revoked, whether the links it attested should lose their attested status
+    pub downgrade_links: bool,
+}
+
+impl Attestor {
+    pub const SIZE: usize = 4 + 32 + 1 + 1;
+}
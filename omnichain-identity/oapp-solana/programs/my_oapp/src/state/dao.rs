@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+// Snapshot of a DAO's EVM holders at a given block, posted by the admin.
+// Only the Merkle root of the (address, weight) pairs is stored on-chain.
+#[account]
+pub struct DaoSnapshot {
+    pub dao: String,             // DAO name, also used as a seed
+    pub block_number: u64,       // EVM block the snapshot was taken at
+    pub chain_reference: String, // EIP-155 chain id of the DAO, e.g. "1"
+    pub merkle_root: [u8; 32],   // see identity_core::merkle for the tree format
+    pub expires_at: i64,         // Memberships proven against this snapshot expire with it
+    pub require_attested: bool,  // Only count attested links, not imported ones
+    pub bump: u8,
+}
+
+impl DaoSnapshot {
+    // A seed can't be longer than 32 bytes
+    pub const MAX_DAO_LENGTH: usize = 32;
+    pub const SIZE: usize = 8 + // discriminator
+        4 + Self::MAX_DAO_LENGTH + // dao
+        8 + // block_number
+        4 + identity_core::MAX_CHAIN_REFERENCE_LENGTH + // chain_reference
+        32 + 8 + 1 + 1;
+}
+
+// Proof that a Solana wallet held weight in a DAO snapshot through one of its
+// verified EVM links. There is one per holder, at
+// [MEMBERSHIP_SEED, snapshot, evm_address]: other programs must check
+// `authority` and `expires_at`.
+#[account]
+pub struct Membership {
+    pub authority: Pubkey,     // Solana wallet that proved membership
+    pub snapshot: Pubkey,      // DaoSnapshot the proof was checked against
+    pub evm_address: [u8; 20], // Linked EVM address found in the snapshot
+    pub weight: u64,           // Weight of that address in the snapshot
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl Membership {
+    pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
+
+    // Hands `info` over to `new_authority` if it is a membership of `authority`,
+    // for instructions that move an identity, as its links go along. Returns
+    // whether it did.
+    pub fn move_if_of<'info>(
+        info: &'info AccountInfo<'info>,
+        authority: &Pubkey,
+        new_authority: &Pubkey,
+    ) -> Result<bool> {
+        // `Account::try_from` also checks that this program owns it
+        let Ok(mut membership) = Account::<Membership>::try_from(info) else {
+            return Ok(false);
+        };
+        if membership.authority != *authority || !info.is_writable {
+            return Ok(false);
+        }
+        membership.authority = *new_authority;
+        membership.exit(&crate::ID)?;
+        Ok(true)
+    }
+}
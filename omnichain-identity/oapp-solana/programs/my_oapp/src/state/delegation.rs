@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::MyOAppError;
+
+// What a delegate may do with an identity's links
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum DelegationScope {
+    Add,          // add_linked_address, set_primary_address
+    Remove,       // remove_linked_address, set_link_expiry
+    AddAndRemove, // Both of the above
+}
+
+impl DelegationScope {
+    // Whether this scope covers `needed`, which is `Add` or `Remove`
+    pub fn allows(&self, needed: DelegationScope) -> bool {
+        *self == DelegationScope::AddAndRemove || *self == needed
+    }
+}
+
+// Lets a delegate, such as a smart wallet or custodian, change an identity's
+// links without its authority: [DELEGATION_SEED, identity account, delegate].
+// Grants are replaced by granting again and removed with `revoke_delegation`.
+#[account]
+pub struct Delegation {
+    pub identity: Pubkey, // IdentityAccount the grant is for
+    pub delegate: Pubkey,
+    pub scope: DelegationScope,
+    pub expires_at: i64, // Unix timestamp from which the grant no longer counts
+    pub bump: u8,
+}
+
+impl Delegation {
+    pub const SIZE: usize = 8 + 32 + 32 + 1 + 8 + 1;
+
+    // Checks that the grant covers `needed` at `now`
+    pub fn check(&self, needed: DelegationScope, now: i64) -> Result<()> {
+        if now >= self.expires_at {
+            return Err(error!(MyOAppError::DelegationExpired));
+        }
+        if !self.scope.allows(needed) {
+            return Err(error!(MyOAppError::InvalidDelegation));
+        }
+        Ok(())
+    }
+}
@@ -1,19 +1,376 @@
 use anchor_lang::prelude::*;
+use identity_core::{
+    AddressNamespace, ChainAddress, EvmCommand, EvmSignature, IdentityError, LinkExpiry,
+    LinkVerification, LinkedAddress, PendingLink, IDENTITY_SEED,
+};
 
-// Account state for storing linked identities
+use crate::errors::MyOAppError;
+use crate::pda;
+use crate::state::{Delegation, DelegationScope, RecoveryConfig};
+
+// Account state for storing linked identities.
+// The layout is shared with the other identity programs through `identity_core`.
 #[account]
 pub struct IdentityAccount {
-    pub authority: Pubkey,                // Solana wallet owner
-    pub linked_addresses: Vec<String>,    // List of EVM addresses
-    pub bump: u8,                         // Canonical bump
+    pub authority: Pubkey,                     // Solana wallet owner
+    pub linked_addresses: Vec<LinkedAddress>,  // Linked addresses, tagged by namespace and chain
+    pub bump: u8,                              // Canonical bump
+    pub primary: Option<u8>,                   // Index of the primary EVM link, if any
+    pub evm_manager: Option<[u8; 20]>,         // Verified EVM link allowed to sign commands
+    pub evm_nonce: u64,                        // Nonce of the next EVM command
+    pub frozen: bool,                          // Set by the EVM manager to lock out the authority
+    pub link_expiries: Vec<LinkExpiry>,        // Expiry of time-limited links, by link index
+    pub attestation_nonce: u64,                // Nonce the next link attestation must be signed for
+    pub pending_links: Vec<PendingLink>,       // Links requested from EVM, awaiting the authority
+    pub open_grants: u8,                       // Delegations and recovery config not closed yet
 }
 
 impl IdentityAccount {
-    pub const MAX_ADDRESS_LENGTH: usize = 42;   // Standard Ethereum address is 42 chars with 0x prefix
-    pub const MAX_ADDRESSES: usize = 10;        // Allow up to 10 linked addresses
-    pub const SIZE: usize = 8 +                 // Discriminator
-                            32 +                // authority: Pubkey
-                            1 +                 // bump: u8
-                            4 +                 // vec length
-                            (Self::MAX_ADDRESS_LENGTH * Self::MAX_ADDRESSES); // addresses storage
+    pub const MAX_ADDRESSES: usize = identity_core::MAX_LINKED_ADDRESSES;
+    pub const MAX_PENDING_LINKS: usize = identity_core::MAX_PENDING_LINKS;
+    pub const SIZE: usize = identity_core::IDENTITY_ACCOUNT_SIZE;
+    // Paid to whoever runs `prune_expired`, out of the rent it frees
+    pub const PRUNE_TIP: u64 = 5_000;
+
+    // Loads the identity of `authority` for an instruction that may also create
+    // it, like an attestation or an import claim for a wallet that never called
+    // `init_identity`. `init_if_needed` can't be used: it rejects existing
+    // accounts of another size than `SIZE`, and `prune_expired` shrinks them.
+    // Existing accounts are grown back to `SIZE` instead; write the identity
+    // back with `try_serialize` once done.
+    pub fn load_or_create<'info>(
+        payer: &AccountInfo<'info>,
+        identity_info: &AccountInfo<'info>,
+        tombstone: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        authority: Pubkey,
+        bump: u8,
+    ) -> Result<Self> {
+        if identity_info.owner == &crate::ID && !identity_info.data_is_empty() {
+            pda::grow_account(
+                payer.clone(),
+                identity_info.clone(),
+                system_program.clone(),
+                Self::SIZE,
+            )?;
+            return Self::try_deserialize(&mut &identity_info.try_borrow_data()?[..]);
+        }
+
+        // A closed identity only comes back through `reopen_identity`
+        if !tombstone.data_is_empty() {
+            return Err(error!(MyOAppError::IdentityClosed));
+        }
+        pda::create_pda(
+            payer.clone(),
+            identity_info.clone(),
+            system_program.clone(),
+            &[IDENTITY_SEED, authority.as_ref(), &[bump]],
+            Self::SIZE,
+        )?;
+        Ok(Self {
+            authority,
+            linked_addresses: Vec::new(),
+            bump,
+            primary: None,
+            evm_manager: None,
+            evm_nonce: 0,
+            frozen: false,
+            link_expiries: Vec::new(),
+            attestation_nonce: 0,
+            pending_links: Vec::new(),
+            open_grants: 0,
+        })
+    }
+
+    // Record a link vouched for by something other than the authority: an
+    // existing link for the same address and chain takes the new verification,
+    // otherwise the link is added.
+    pub fn record_verified_link(&mut self, link: LinkedAddress) -> Result<()> {
+        match self.linked_addresses.iter_mut().find(|linked| {
+            linked.address == link.address && linked.chain_reference == link.chain_reference
+        }) {
+            Some(existing) => existing.verification = link.verification,
+            None => {
+                if self.linked_addresses.len() >= Self::MAX_ADDRESSES {
+                    msg!("Maximum number of linked addresses reached");
+                    return Err(error!(IdentityError::TooManyLinkedAddresses));
+                }
+                self.linked_addresses.push(link);
+            },
+        }
+        Ok(())
+    }
+
+    // The verification of the link for exactly this address and chain, if any
+    pub fn verification_of(&self, link: &LinkedAddress) -> Option<LinkVerification> {
+        self.linked_addresses
+            .iter()
+            .find(|linked| {
+                linked.address == link.address && linked.chain_reference == link.chain_reference
+            })
+            .map(|linked| linked.verification)
+    }
+
+    // This identity under a new authority, for moving it to that authority's PDA.
+    // `cleared_tombstone` is the attestation nonce of the new authority's
+    // tombstone, if it had one, see `IdentityTombstone::clear_if_exists`.
+    pub fn moved_to(&self, authority: Pubkey, bump: u8, cleared_tombstone: Option<u64>) -> Self {
+        let attestation_nonce = self.attestation_nonce.max(cleared_tombstone.unwrap_or(0));
+        // Grants are found by the old identity's address and stay behind with it
+        Self { authority, bump, attestation_nonce, open_grants: 0, ..self.clone() }
+    }
+
+    // Counts a delegation or recovery config created for this identity. Each
+    // one must be closed before the identity is, as they are found by the
+    // identity's address and would apply again to a later identity there.
+    pub fn open_grant(&mut self) -> Result<()> {
+        self.open_grants =
+            self.open_grants.checked_add(1).ok_or_else(|| error!(MyOAppError::TooManyGrants))?;
+        Ok(())
+    }
+
+    pub fn close_grant(&mut self) {
+        self.open_grants = self.open_grants.saturating_sub(1);
+    }
+
+    // Closes `info` if it is a writable delegation or recovery config of the
+    // identity at `identity`, refunding its rent to `destination`. Returns
+    // whether it did.
+    pub fn close_grant_account<'info>(
+        &mut self,
+        identity: &Pubkey,
+        info: &'info AccountInfo<'info>,
+        destination: &AccountInfo<'info>,
+    ) -> Result<bool> {
+        // `Account::try_from` also checks that this program owns it
+        let is_grant = Account::<Delegation>::try_from(info)
+            .is_ok_and(|delegation| delegation.identity == *identity)
+            || Account::<RecoveryConfig>::try_from(info)
+                .is_ok_and(|recovery| recovery.identity == *identity);
+        if !is_grant || !info.is_writable {
+            return Ok(false);
+        }
+        pda::close_pda(info, destination)?;
+        self.close_grant();
+        Ok(true)
+    }
+
+    // For instructions that retire the identity
+    pub fn assert_no_open_grants(&self) -> Result<()> {
+        if self.open_grants > 0 {
+            msg!("{} delegations or recovery configs are still open", self.open_grants);
+            return Err(error!(MyOAppError::IdentityHasOpenGrants));
+        }
+        Ok(())
+    }
+
+    // The primary EVM link, see `set_primary`
+    pub fn primary_link(&self) -> Option<&LinkedAddress> {
+        self.primary.and_then(|index| self.linked_addresses.get(index as usize))
+    }
+
+    // Make the link at `index` the primary one. Only EVM links can be primary.
+    pub fn set_primary(&mut self, index: usize) -> Result<()> {
+        match self.linked_addresses.get(index) {
+            Some(link) if link.address.namespace() == AddressNamespace::Evm => {
+                self.primary = Some(index as u8);
+                Ok(())
+            },
+            Some(_) => Err(error!(MyOAppError::PrimaryNotEvm)),
+            None => Err(error!(MyOAppError::AddressNotLinked)),
+        }
+    }
+
+    // Remove the link at `index`. Later links move down by one, and when the
+    // primary link goes the first remaining verified EVM link becomes primary.
+    // The EVM manager loses its rights once its address has no verified link left.
+    pub fn remove_link(&mut self, index: usize) -> LinkedAddress {
+        let removed = self.linked_addresses.remove(index);
+        self.link_expiries.retain(|expiry| expiry.index as usize != index);
+        for expiry in self.link_expiries.iter_mut().filter(|expiry| expiry.index as usize > index) {
+            expiry.index -= 1;
+        }
+        if let Some(manager) = self.evm_manager {
+            if !self.has_verified_evm_link(&manager) {
+                self.evm_manager = None;
+            }
+        }
+        self.primary = match self.primary.map(|primary| primary as usize) {
+            Some(primary) if primary == index => self.fallback_primary(),
+            Some(primary) if primary > index => Some(primary as u8 - 1),
+            primary => primary.map(|primary| primary as u8),
+        };
+        removed
+    }
+
+    // The first verified EVM link, which takes over from a primary link that is
+    // removed or downgraded
+    pub fn fallback_primary(&self) -> Option<u8> {
+        self.linked_addresses
+            .iter()
+            .position(|link| {
+                link.address.namespace() == AddressNamespace::Evm
+                    && link.verification.is_verified()
+            })
+            .map(|position| position as u8)
+    }
+
+    // Index of the link for exactly this address and chain, if any
+    pub fn position_of(&self, link: &LinkedAddress) -> Option<usize> {
+        self.linked_addresses.iter().position(|linked| {
+            linked.address == link.address && linked.chain_reference == link.chain_reference
+        })
+    }
+
+    // When the link at `index` expires, None for links that don't
+    pub fn expires_at(&self, index: usize) -> Option<i64> {
+        self.link_expiries
+            .iter()
+            .find(|expiry| expiry.index as usize == index)
+            .map(|expiry| expiry.expires_at)
+    }
+
+    // Whether the link at `index` still counts at `now`
+    pub fn is_active(&self, index: usize, now: i64) -> bool {
+        match self.expires_at(index) {
+            Some(expires_at) => now < expires_at,
+            None => true,
+        }
+    }
+
+    // Links that haven't expired at `now`
+    pub fn active_links(&self, now: i64) -> impl Iterator<Item = &LinkedAddress> {
+        self.linked_addresses
+            .iter()
+            .enumerate()
+            .filter(move |(index, _)| self.is_active(*index, now))
+            .map(|(_, link)| link)
+    }
+
+    // Set or clear the expiry of the link at `index`
+    pub fn set_expiry(&mut self, index: usize, expires_at: Option<i64>) {
+        self.link_expiries.retain(|expiry| expiry.index as usize != index);
+        if let Some(expires_at) = expires_at {
+            self.link_expiries.push(LinkExpiry { index: index as u8, expires_at });
+        }
+    }
+
+    // Remove every link that expired at `now`, returning how many went
+    pub fn prune_expired(&mut self, now: i64) -> usize {
+        let mut expired: Vec<usize> = self
+            .link_expiries
+            .iter()
+            .filter(|expiry| now >= expiry.expires_at)
+            .map(|expiry| expiry.index as usize)
+            .collect();
+        // From the last index down, so removals don't shift the ones still to go
+        expired.sort_unstable_by(|a, b| b.cmp(a));
+        for index in &expired {
+            self.remove_link(*index);
+        }
+        expired.len()
+    }
+
+    // Checks that `signer` may make a change needing `needed`: the authority
+    // always can, a delegate only with a live grant that covers it
+    pub fn check_manager(
+        &self,
+        signer: &Pubkey,
+        delegation: Option<&Delegation>,
+        needed: DelegationScope,
+        now: i64,
+    ) -> Result<()> {
+        if *signer == self.authority {
+            return Ok(());
+        }
+        match delegation {
+            Some(delegation) if delegation.delegate == *signer => delegation.check(needed, now),
+            _ => Err(error!(IdentityError::InvalidAddress)),
+        }
+    }
+
+    // Whether `evm_address` is linked on any chain with a verified link
+    pub fn has_verified_evm_link(&self, evm_address: &[u8; 20]) -> bool {
+        self.linked_addresses.iter().any(|link| {
+            link.address == ChainAddress::Evm(*evm_address) && link.verification.is_verified()
+        })
+    }
+
+    // Whether `evm_address` has a verified link that hasn't expired at `now`
+    pub fn has_active_verified_evm_link(&self, evm_address: &[u8; 20], now: i64) -> bool {
+        self.linked_addresses.iter().enumerate().any(|(index, link)| {
+            link.address == ChainAddress::Evm(*evm_address)
+                && link.verification.is_verified()
+                && self.is_active(index, now)
+        })
+    }
+
+    // Checks that the EVM manager still has a verified, unexpired link and
+    // signed `command` with the current nonce, then uses up the nonce. Returns
+    // the manager's address.
+    pub fn authorize_evm_command(
+        &mut self,
+        command: &EvmCommand,
+        signature: &EvmSignature,
+        now: i64,
+    ) -> Result<[u8; 20]> {
+        let manager = self.evm_manager.ok_or_else(|| error!(MyOAppError::EvmManagerNotSet))?;
+        if !self.has_active_verified_evm_link(&manager, now) {
+            return Err(error!(MyOAppError::EvmManagerNotVerified));
+        }
+        let message = identity_core::evm_command_message(
+            &crate::ID,
+            &self.authority,
+            self.evm_nonce,
+            command,
+        );
+        if identity_core::recover_evm_signer(&message, signature)? != manager {
+            return Err(error!(IdentityError::InvalidSignature));
+        }
+        self.evm_nonce += 1;
+        Ok(manager)
+    }
+}
+
+// Left behind at [TOMBSTONE_SEED, authority] by `close_identity`, and by the
+// rotations and recoveries that move an identity away from the wallet. While it
+// exists no identity is created for the wallet, whether by `init_identity` or by
+// a late attestation or import claim; `reopen_identity` removes it, as does
+// moving another identity to the wallet.
+#[account]
+pub struct IdentityTombstone {
+    pub authority: Pubkey,
+    pub closed_at: i64,
+    pub bump: u8,
+    pub attestation_nonce: u64, // Carried over to the reopened identity, so old attestations stay spent
+}
+
+impl IdentityTombstone {
+    pub const SIZE: usize = 8 + 32 + 8 + 1 + 8;
+
+    // Records the end of the identity of `authority`. The tombstone may be left
+    // from an earlier identity of the wallet, whose attestations stay spent too.
+    pub fn record(&mut self, authority: Pubkey, attestation_nonce: u64, bump: u8) -> Result<()> {
+        self.authority = authority;
+        self.closed_at = Clock::get()?.unix_timestamp;
+        self.bump = bump;
+        self.attestation_nonce = self.attestation_nonce.max(attestation_nonce);
+        Ok(())
+    }
+
+    // Removes the tombstone at `info` if there is one, for instructions that
+    // move an identity to its wallet, refunding its rent to `destination`.
+    // Returns the attestation nonce it kept, which the moved identity must not
+    // go below.
+    pub fn clear_if_exists<'info>(
+        info: &AccountInfo<'info>,
+        destination: &AccountInfo<'info>,
+    ) -> Result<Option<u64>> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(None);
+        }
+        let tombstone = Self::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        pda::close_pda(info, destination)?;
+        Ok(Some(tombstone.attestation_nonce))
+    }
 }
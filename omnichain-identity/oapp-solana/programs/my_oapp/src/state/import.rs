@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+// Bulk import of identity links collected off-chain, published by the admin as
+// a Merkle root of (index, solana pubkey, evm address) entries.
+#[account]
+pub struct LinkImport {
+    pub import_id: u32,
+    pub merkle_root: [u8; 32], // see identity_core::merkle for the tree format
+    pub entry_count: u64,      // Number of entries in the tree, indexes are 0..entry_count
+    pub bump: u8,
+}
+
+impl LinkImport {
+    pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
+}
+
+// Claim bitmap for one chunk of an import's entries. Bit `i` of chunk `c` is
+// set once entry `c * CLAIMS_PER_CHUNK + i` has been claimed.
+#[account]
+pub struct ImportClaims {
+    pub link_import: Pubkey,
+    pub chunk: u32,
+    pub bitmap: [u8; ImportClaims::BITMAP_BYTES],
+    pub bump: u8,
+}
+
+impl ImportClaims {
+    pub const BITMAP_BYTES: usize = 512;
+    pub const CLAIMS_PER_CHUNK: u64 = Self::BITMAP_BYTES as u64 * 8;
+    pub const SIZE: usize = 8 + 32 + 4 + Self::BITMAP_BYTES + 1;
+
+    pub fn chunk_of(index: u64) -> u32 {
+        (index / Self::CLAIMS_PER_CHUNK) as u32
+    }
+
+    // Sets the claim bit of `index`, returning false if it was already set
+    pub fn claim(&mut self, index: u64) -> bool {
+        let bit = (index % Self::CLAIMS_PER_CHUNK) as usize;
+        let mask = 1u8 << (bit % 8);
+        if self.bitmap[bit / 8] & mask != 0 {
+            return false;
+        }
+        self.bitmap[bit / 8] |= mask;
+        true
+    }
+}
@@ -1,7 +1,23 @@
 pub mod store;
 mod peer_config;
 pub mod identity;
+mod attestor;
+pub mod dao;
+pub mod import;
+pub mod query;
+pub mod reassembly;
+pub mod treasury;
+pub mod recovery;
+pub mod delegation;
 
 pub use store::*; 
 pub use peer_config::*;
 pub use identity::*;
+pub use attestor::*;
+pub use dao::*;
+pub use import::*;
+pub use query::*;
+pub use reassembly::*;
+pub use treasury::*;
+pub use recovery::*;
+pub use delegation::*;
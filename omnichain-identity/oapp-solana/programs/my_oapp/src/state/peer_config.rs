@@ -1,17 +1,149 @@
 use crate::*;
+use identity_core::{ExecutionOptions, MessageType};
 
 pub const ENFORCED_OPTIONS_SEND_MAX_LEN: usize = 512;
 pub const ENFORCED_OPTIONS_SEND_AND_CALL_MAX_LEN: usize = 1024;
+pub const ENFORCED_OPTIONS_MESSAGE_TYPE_MAX_LEN: usize = 256;
+// One entry per typed message
+pub const MAX_MESSAGE_TYPE_OPTIONS: usize = 7;
+pub const MAX_TRUSTED_SENDERS: usize = 4;
 
 #[account]
+#[derive(InitSpace)]
 pub struct PeerConfig {
     pub peer_address: [u8; 32],
     pub enforced_options: EnforcedOptions,
     pub bump: u8,
+    // Enforced options of typed messages, keyed by message type. Kept after
+    // `bump` so peers configured before it existed still deserialize.
+    #[max_len(MAX_MESSAGE_TYPE_OPTIONS)]
+    pub message_type_options: Vec<MessageTypeOptions>,
+    // Senders accepted next to `peer_address`, such as a previous linker
+    // deployment whose messages are still in flight. Outbound sends only ever
+    // go to `peer_address`.
+    #[max_len(MAX_TRUSTED_SENDERS)]
+    pub trusted_senders: Vec<TrustedSender>,
+    // Disabled peers neither send nor receive, see `disable_peer`
+    pub disabled: bool,
+    // Least lzReceive gas outbound messages must carry, keyed by message type.
+    // Messages without an entry aren't checked.
+    #[max_len(MAX_MESSAGE_TYPE_OPTIONS + 1)]
+    pub min_lz_receive_gas: Vec<MinLzReceiveGas>,
 }
 
 impl PeerConfig {
-    pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
+    pub const SIZE: usize = 8 + Self::INIT_SPACE;
+
+    // Loads the peer of `remote_eid`, creating it on first configuration.
+    // `init_if_needed` can't be used: peers configured before later fields were
+    // appended are smaller than `SIZE` and would fail to deserialize. Existing
+    // peers are grown to `SIZE` first; write the peer back with `try_serialize`
+    // once done.
+    pub fn load_or_create<'info>(
+        payer: &AccountInfo<'info>,
+        peer_info: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        store: &Pubkey,
+        remote_eid: u32,
+        bump: u8,
+    ) -> Result<Self> {
+        if peer_info.owner == &crate::ID && !peer_info.data_is_empty() {
+            pda::grow_account(
+                payer.clone(),
+                peer_info.clone(),
+                system_program.clone(),
+                Self::SIZE,
+            )?;
+            return Self::try_deserialize(&mut &peer_info.try_borrow_data()?[..]);
+        }
+
+        pda::create_pda(
+            payer.clone(),
+            peer_info.clone(),
+            system_program.clone(),
+            &[PEER_SEED, &store.to_bytes(), &remote_eid.to_be_bytes(), &[bump]],
+            Self::SIZE,
+        )?;
+        Ok(Self {
+            peer_address: [0; 32],
+            enforced_options: EnforcedOptions::default(),
+            bump,
+            message_type_options: Vec::new(),
+            trusted_senders: Vec::new(),
+            disabled: false,
+            min_lz_receive_gas: Vec::new(),
+        })
+    }
+
+    // Enforced options for a message: the entry of its type when there is one,
+    // otherwise the `send` options, which also cover untyped string messages
+    pub fn get_enforced_options(&self, message_type: Option<MessageType>) -> Vec<u8> {
+        message_type
+            .and_then(|message_type| {
+                self.message_type_options
+                    .iter()
+                    .find(|entry| entry.msg_type == message_type as u8)
+            })
+            .map(|entry| entry.options.clone())
+            .unwrap_or_else(|| self.enforced_options.get_enforced_options(&None))
+    }
+
+    pub fn combine_options(
+        &self,
+        message_type: Option<MessageType>,
+        extra_options: &Vec<u8>,
+    ) -> Result<Vec<u8>> {
+        let enforced_options = self.get_enforced_options(message_type);
+        oapp::options::combine_options(enforced_options, extra_options)
+    }
+
+    // Whether `lz_receive` accepts a message from `sender` at `now`
+    pub fn is_trusted_sender(&self, sender: &[u8; 32], now: i64) -> bool {
+        *sender == self.peer_address
+            || self
+                .trusted_senders
+                .iter()
+                .any(|trusted| trusted.address == *sender && !trusted.is_sunset(now))
+    }
+
+    // Adds a trusted sender or updates its sunset
+    pub fn set_trusted_sender(&mut self, address: [u8; 32], sunset: Option<i64>) -> Result<()> {
+        if let Some(trusted) = self.trusted_senders.iter_mut().find(|t| t.address == address) {
+            trusted.sunset = sunset;
+            return Ok(());
+        }
+        if self.trusted_senders.len() >= MAX_TRUSTED_SENDERS {
+            return Err(error!(MyOAppError::TooManyTrustedSenders));
+        }
+        self.trusted_senders.push(TrustedSender { address, sunset });
+        Ok(())
+    }
+
+    // Fails when `options` carry less lzReceive gas than the minimum set for
+    // the message type, so a message can't be sent that would never execute
+    pub fn assert_options(&self, message_type: Option<MessageType>, options: &[u8]) -> Result<()> {
+        let msg_type = MinLzReceiveGas::key(message_type);
+        match self.min_lz_receive_gas.iter().find(|entry| entry.msg_type == msg_type) {
+            Some(entry) => ExecutionOptions::decode(options)?.assert_min_lz_receive_gas(entry.gas),
+            None => Ok(()),
+        }
+    }
+
+    // Replaces the minimum of a message type, zero removes it
+    pub fn set_min_lz_receive_gas(&mut self, msg_type: u8, gas: u128) {
+        self.min_lz_receive_gas.retain(|entry| entry.msg_type != msg_type);
+        if gas > 0 {
+            self.min_lz_receive_gas.push(MinLzReceiveGas { msg_type, gas });
+        }
+    }
+
+    // Replaces the entry of a message type, empty options remove it
+    pub fn set_message_type_options(&mut self, msg_type: u8, options: Vec<u8>) {
+        self.message_type_options.retain(|entry| entry.msg_type != msg_type);
+        if !options.is_empty() {
+            self.message_type_options.push(MessageTypeOptions { msg_type, options });
+        }
+    }
 }
 
 #[derive(Clone, Default, AnchorSerialize, AnchorDeserialize, InitSpace)]
@@ -30,13 +162,35 @@ impl EnforcedOptions {
             self.send_and_call.clone()
         }
     }
+}
 
-    pub fn combine_options(
-        &self,
-        compose_msg: &Option<Vec<u8>>,
-        extra_options: &Vec<u8>,
-    ) -> Result<Vec<u8>> {
-        let enforced_options = self.get_enforced_options(compose_msg);
-        oapp::options::combine_options(enforced_options, extra_options)
+#[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct MessageTypeOptions {
+    pub msg_type: u8,
+    #[max_len(ENFORCED_OPTIONS_MESSAGE_TYPE_MAX_LEN)]
+    pub options: Vec<u8>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct TrustedSender {
+    pub address: [u8; 32],
+    pub sunset: Option<i64>, // No longer trusted from this timestamp on, None for never
+}
+
+impl TrustedSender {
+    pub fn is_sunset(&self, now: i64) -> bool {
+        self.sunset.is_some_and(|sunset| now >= sunset)
+    }
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct MinLzReceiveGas {
+    pub msg_type: u8, // Message type, 0 for the string messages of `send`
+    pub gas: u128,
+}
+
+impl MinLzReceiveGas {
+    pub fn key(message_type: Option<MessageType>) -> u8 {
+        message_type.map_or(0, |message_type| message_type as u8)
     }
 }
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use identity_core::{LinkVerification, VerifyResponse};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum QueryStatus {
+    Pending,
+    Fulfilled,
+    // The response arrived after the deadline and was discarded
+    TimedOut,
+}
+
+// A query sent to an EVM peer, waiting for its response. Keyed by the guid of
+// the outbound message: [PENDING_QUERY_SEED, guid]. Consumers can poll
+// `status`, or be notified through compose when the response arrives.
+#[account]
+pub struct PendingQuery {
+    pub requester: Pubkey,
+    pub guid: [u8; 32],
+    pub dst_eid: u32,          // EVM chain the query was sent to
+    pub evm_address: [u8; 20], // EVM account whose links were queried
+    pub compose_to: Pubkey,    // Program notified on response, default if none
+    pub deadline: i64,         // Responses after this time are discarded
+    pub status: QueryStatus,
+    pub solana_addresses: Vec<Pubkey>, // Filled from the response
+    pub bump: u8,
+}
+
+impl PendingQuery {
+    // Responses listing more addresses are truncated
+    pub const MAX_RESULTS: usize = 8;
+    // Longest a query may wait for its response, in seconds
+    pub const MAX_TIMEOUT: i64 = 7 * 24 * 60 * 60;
+    pub const SIZE: usize = 8 + // discriminator
+        32 + 32 + 4 + 20 + 32 + 8 + 1 + // fixed fields
+        4 + Self::MAX_RESULTS * 32 + // solana_addresses
+        1; // bump
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.deadline
+    }
+}
+
+// Answer to a verification query from an EVM peer, waiting to be sent back by
+// `send_verification_response`. Keyed by the guid of the inbound query:
+// [VERIFICATION_RESPONSE_SEED, guid]. Holds the response fee delivered with
+// the query until it is paid to whoever sends the answer. The rent goes back
+// to the executor that created the PDA.
+#[account]
+pub struct VerificationResponse {
+    pub src_eid: u32, // EVM chain the query came from, where the answer goes
+    pub request_guid: [u8; 32],
+    pub evm_address: [u8; 20],
+    pub solana_address: Pubkey,
+    pub verification: Option<LinkVerification>, // Strongest covering link, None if not linked
+    pub response_fee: u64,
+    pub payer: Pubkey, // Executor that created the PDA and forwarded the response fee
+    pub bump: u8,
+}
+
+impl VerificationResponse {
+    pub const SIZE: usize = 8 + // discriminator
+        4 + 32 + 20 + 32 + // src_eid, request_guid, evm_address, solana_address
+        1 + LinkVerification::SIZE + // verification
+        8 + 32 + 1; // response_fee, payer, bump
+
+    pub fn to_verify_response(&self) -> VerifyResponse {
+        VerifyResponse {
+            request_guid: self.request_guid,
+            evm_address: self.evm_address,
+            solana_address: self.solana_address,
+            verification: self.verification,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use identity_core::CHUNK_DATA_SIZE;
+
+// Chunks of a message received so far, keyed by
+// [REASSEMBLY_SEED, src eid, message id]. Once every chunk is there, the
+// payload is handled by `process_reassembled_message`. Buffers that never
+// complete can be reclaimed by anyone after `TIMEOUT`.
+#[account]
+pub struct ReassemblyBuffer {
+    pub src_eid: u32,
+    pub message_id: [u8; 32],
+    pub payer: Pubkey, // Paid the rent, refunded when the buffer is closed
+    pub created_at: i64,
+    pub total_chunks: u16,
+    pub received: u16,    // Bit i is set once chunk i arrived
+    pub payload_len: u32, // Known once the last chunk arrived
+    pub payload: Vec<u8>, // Chunk i is at i * CHUNK_DATA_SIZE
+    pub bump: u8,
+}
+
+impl ReassemblyBuffer {
+    pub const TIMEOUT: i64 = 24 * 60 * 60;
+
+    // The buffer is sized for the number of chunks of its message
+    pub fn space(total_chunks: u16) -> usize {
+        8 + // discriminator
+        4 + 32 + 32 + 8 + 2 + 2 + 4 + // header
+        4 + total_chunks as usize * CHUNK_DATA_SIZE + // payload
+        1 // bump
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.received.count_ones() == self.total_chunks as u32
+    }
+
+    pub fn has_chunk(&self, index: u16) -> bool {
+        self.received & (1 << index) != 0
+    }
+
+    // Copy a chunk in place. The caller checks it wasn't received yet.
+    pub fn write_chunk(&mut self, index: u16, data: &[u8]) {
+        let offset = index as usize * CHUNK_DATA_SIZE;
+        self.payload[offset..offset + data.len()].copy_from_slice(data);
+        self.received |= 1 << index;
+        if index + 1 == self.total_chunks {
+            self.payload_len = (offset + data.len()) as u32;
+        }
+    }
+
+    pub fn reassembled(&self) -> &[u8] {
+        &self.payload[..self.payload_len as usize]
+    }
+}
@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+
+use crate::pda;
+
+// Guardians that can move an identity to a new wallet when its authority key
+// is lost: [RECOVERY_SEED, identity account]. Each guardian backs at most one
+// new authority at a time. Once `threshold` guardians back the same one, the
+// recovery can be executed after `timelock` seconds, unless the authority
+// cancels it or enough guardians move their approval elsewhere first.
+#[account]
+pub struct RecoveryConfig {
+    pub identity: Pubkey, // IdentityAccount this config protects
+    pub guardians: Vec<Pubkey>,
+    pub votes: Vec<Option<Pubkey>>, // New authority each guardian backs, by guardian index
+    pub threshold: u8,              // Approvals needed to start the timelock, a majority
+    pub timelock: i64,              // Seconds between reaching the threshold and executing
+    pub pending: Option<PendingRecovery>, // Recovery the threshold backs, in its timelock
+    pub bump: u8,
+}
+
+impl RecoveryConfig {
+    pub const MAX_GUARDIANS: usize = 5;
+    pub const MIN_TIMELOCK: i64 = 24 * 60 * 60;
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // identity
+        4 + Self::MAX_GUARDIANS * 32 + // guardians
+        4 + Self::MAX_GUARDIANS * 33 + // votes
+        1 + 8 + // threshold, timelock
+        1 + PendingRecovery::SIZE + // pending
+        1;
+
+    // Closes the recovery config at `info` if there is one, for instructions
+    // that retire its identity: guardians must not carry over to a later
+    // identity at the same address. Returns whether there was one.
+    pub fn close_if_exists<'info>(
+        info: &AccountInfo<'info>,
+        destination: &AccountInfo<'info>,
+    ) -> Result<bool> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(false);
+        }
+        pda::close_pda(info, destination)?;
+        Ok(true)
+    }
+
+    pub fn guardian_index(&self, guardian: &Pubkey) -> Option<usize> {
+        self.guardians.iter().position(|g| g == guardian)
+    }
+
+    pub fn votes_for(&self, new_authority: &Pubkey) -> u32 {
+        self.votes.iter().filter(|vote| vote.as_ref() == Some(new_authority)).count() as u32
+    }
+
+    // Records what the guardian at `index` backs, None withdrawing its approval,
+    // then settles the pending recovery. A pending recovery keeps its timelock
+    // while the threshold still backs it and is dropped once it doesn't, which
+    // lets the other guardians outvote a guardian that opened a bad one. As the
+    // threshold is a majority, only one new authority can reach it at a time.
+    pub fn vote(&mut self, index: usize, new_authority: Option<Pubkey>, now: i64) {
+        self.votes.resize(self.guardians.len(), None);
+        self.votes[index] = new_authority;
+
+        let threshold = self.threshold as u32;
+        if let Some(pending) = self.pending {
+            if self.votes_for(&pending.new_authority) >= threshold {
+                return;
+            }
+            self.pending = None;
+        }
+        if let Some(new_authority) = new_authority {
+            if self.votes_for(&new_authority) >= threshold {
+                self.pending =
+                    Some(PendingRecovery { new_authority, executable_at: now + self.timelock });
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub struct PendingRecovery {
+    pub new_authority: Pubkey,
+    pub executable_at: i64, // Timelock end, set when the threshold was reached
+}
+
+impl PendingRecovery {
+    pub const SIZE: usize = 32 + 8;
+}
@@ -7,11 +7,53 @@ pub struct Store {
     pub endpoint_program: Pubkey, // This is required and should be consistent.
     pub string: String, // This is specific to this string-passing example.
     // You can add more fields as needed for your OApp implementation.
+    pub attestors: Vec<Attestor>, // Trusted attestors, managed by the admin.
+    pub next_attestor_id: u32,
+    pub sponsorship: SponsorshipConfig, // Limits on fees paid by the treasury
+    pub peer_eids: Vec<u32>, // Remote eids with a PeerConfig, for tooling to enumerate
 }
 
 impl Store {
     pub const MAX_STRING_LENGTH: usize = 256;
-    pub const SIZE: usize = 8 + std::mem::size_of::<Self>() + Self::MAX_STRING_LENGTH;
+    pub const MAX_ATTESTORS: usize = 16;
+    pub const MAX_PEERS: usize = 32;
+    pub const SIZE: usize = 8 + std::mem::size_of::<Self>() + Self::MAX_STRING_LENGTH
+        + Self::MAX_ATTESTORS * Attestor::SIZE
+        + Self::MAX_PEERS * 4;
+
+    pub fn attestor(&self, attestor_id: u32) -> Result<&Attestor> {
+        self.attestors
+            .iter()
+            .find(|attestor| attestor.id == attestor_id)
+            .ok_or_else(|| error!(MyOAppError::AttestorNotFound))
+    }
+
+    // Whether links attested by `attestor_id` lose their attested status: its
+    // attestor was revoked with `downgrade_links`, or pruned after revocation
+    pub fn downgrades_links_of(&self, attestor_id: u32) -> bool {
+        match self.attestors.iter().find(|attestor| attestor.id == attestor_id) {
+            Some(attestor) => attestor.revoked && attestor.downgrade_links,
+            None => attestor_id < self.next_attestor_id,
+        }
+    }
+
+    pub fn attestor_mut(&mut self, attestor_id: u32) -> Result<&mut Attestor> {
+        self.attestors
+            .iter_mut()
+            .find(|attestor| attestor.id == attestor_id)
+            .ok_or_else(|| error!(MyOAppError::AttestorNotFound))
+    }
+
+    pub fn register_peer(&mut self, eid: u32) -> Result<()> {
+        if self.peer_eids.contains(&eid) {
+            return Ok(());
+        }
+        if self.peer_eids.len() >= Self::MAX_PEERS {
+            return Err(error!(MyOAppError::TooManyPeers));
+        }
+        self.peer_eids.push(eid);
+        Ok(())
+    }
 }
 
 // The LzReceiveTypesAccounts PDA is used by the Executor as a prerequisite to calling `lz_receive`.
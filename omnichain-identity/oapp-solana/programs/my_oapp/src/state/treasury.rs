@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+// Limits on fee sponsorship, kept on the Store and set by the admin.
+// Sponsorship is off while `spend_cap` is 0.
+#[derive(Clone, Copy, Default, AnchorSerialize, AnchorDeserialize)]
+pub struct SponsorshipConfig {
+    pub max_sends_per_user: u32, // Sponsored sends each user gets
+    pub max_fee_per_send: u64,   // Highest native fee the treasury pays for one send
+    pub spend_cap: u64,          // Total the treasury may ever spend, in lamports
+}
+
+// Lamports the protocol spends on cross-chain fees for users, funded by the
+// admin. The account holds the funds itself: [TREASURY_SEED].
+#[account]
+pub struct Treasury {
+    pub total_spent: u64,
+    pub sponsored_sends: u64,
+    pub bump: u8,
+}
+
+impl Treasury {
+    pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
+}
+
+// Sponsorship used by one user: [SPONSORSHIP_SEED, user]
+#[account]
+pub struct SponsorshipRecord {
+    pub user: Pubkey,
+    pub sends: u32,
+    pub spent: u64,
+    pub bump: u8,
+}
+
+impl SponsorshipRecord {
+    pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
+}
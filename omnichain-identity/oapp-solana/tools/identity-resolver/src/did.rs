@@ -0,0 +1,346 @@
+use identity_core::{caip2_namespace, AddressNamespace, LinkVerification, LinkedAddress};
+use my_oapp::state::IdentityAccount;
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DID_CONTEXT: &str = "https://www.w3.org/ns/did/v1";
+const BLOCKCHAIN_ACCOUNT_ID_CONTEXT: &str = "https://w3id.org/security#blockchainAccountId";
+const PUBLIC_KEY_BASE58_CONTEXT: &str = "https://w3id.org/security#publicKeyBase58";
+const ED25519_VERIFICATION_KEY_CONTEXT: &str =
+    "https://w3id.org/security#Ed25519VerificationKey2018";
+const SECP256K1_RECOVERY_METHOD_CONTEXT: &str =
+    "https://identity.foundation/EcdsaSecp256k1RecoverySignature2020#EcdsaSecp256k1RecoveryMethod2020";
+const BLOCKCHAIN_VERIFICATION_METHOD_CONTEXT: &str =
+    "https://w3id.org/security#BlockchainVerificationMethod2021";
+
+// Verification method types used by did:pkh
+const ED25519_VERIFICATION_KEY: &str = "Ed25519VerificationKey2018";
+const SECP256K1_RECOVERY_METHOD: &str = "EcdsaSecp256k1RecoveryMethod2020";
+const BLOCKCHAIN_VERIFICATION_METHOD: &str = "BlockchainVerificationMethod2021";
+
+// Solana clusters, identified in CAIP-2 by the first 32 characters of their genesis hash
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolanaCluster {
+    Mainnet,
+    Devnet,
+    Testnet,
+}
+
+impl SolanaCluster {
+    pub fn chain_reference(&self) -> &'static str {
+        match self {
+            SolanaCluster::Mainnet => "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp",
+            SolanaCluster::Devnet => "EtWTRABZaYq6iMfeYKouRu166VU2xqa1",
+            SolanaCluster::Testnet => "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3z",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mainnet" | "mainnet-beta" => Some(SolanaCluster::Mainnet),
+            "devnet" => Some(SolanaCluster::Devnet),
+            "testnet" => Some(SolanaCluster::Testnet),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ResolveOptions {
+    pub cluster: SolanaCluster,
+    // EVM chain id used to render chain agnostic EVM links, since a
+    // blockchainAccountId always names a chain
+    pub default_evm_chain: String,
+    // Unix timestamp to resolve at, links expired by then are left out
+    pub now: i64,
+    // Also list self asserted and downgraded links. Control of those addresses
+    // was never shown to anyone but the authority, so by default they don't
+    // make it into the document.
+    pub include_unverified: bool,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+        ResolveOptions {
+            cluster: SolanaCluster::Mainnet,
+            default_evm_chain: "1".to_string(),
+            now,
+            include_unverified: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    #[serde(rename = "@context")]
+    pub context: Vec<serde_json::Value>,
+    pub id: String,
+    pub controller: String,
+    pub verification_method: Vec<VerificationMethod>,
+    pub authentication: Vec<String>,
+    pub assertion_method: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMethod {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub method_type: String,
+    pub controller: String,
+    pub blockchain_account_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key_base58: Option<String>,
+    // How the link was verified, see `verification_status`. None for the controller.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification: Option<String>,
+    // Unix timestamp from which the link no longer counts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<i64>,
+}
+
+// did:pkh identifier of a Solana authority on the given cluster
+pub fn solana_did(account: &IdentityAccount, cluster: SolanaCluster) -> String {
+    format!("did:pkh:solana:{}:{}", cluster.chain_reference(), account.authority)
+}
+
+// Build the DID document of an identity. The authority's key controls the
+// document; linked addresses can make assertions on its behalf, as long as
+// they haven't expired and, unless `include_unverified` is set, are verified.
+// Methods keep the index of their link in the account, so ids stay stable
+// while other links come and go.
+pub fn resolve(account: &IdentityAccount, options: &ResolveOptions) -> DidDocument {
+    let did = solana_did(account, options.cluster);
+    let controller_id = format!("{}#controller", did);
+
+    let mut verification_method = vec![VerificationMethod {
+        id: controller_id.clone(),
+        method_type: ED25519_VERIFICATION_KEY.to_string(),
+        controller: did.clone(),
+        blockchain_account_id: format!(
+            "solana:{}:{}",
+            options.cluster.chain_reference(),
+            account.authority
+        ),
+        public_key_base58: Some(account.authority.to_string()),
+        verification: None,
+        expires: None,
+    }];
+    // The same filter as `IdentityAccount::active_links`, keeping the index
+    let links = account
+        .linked_addresses
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| account.is_active(*index, options.now))
+        .filter(|(_, link)| options.include_unverified || link.verification.is_verified());
+    for (index, link) in links {
+        verification_method.push(VerificationMethod {
+            id: format!("{}#link-{}", did, index),
+            method_type: method_type(link).to_string(),
+            controller: did.clone(),
+            blockchain_account_id: blockchain_account_id(link, options),
+            public_key_base58: None,
+            verification: Some(verification_status(&link.verification)),
+            expires: account.expires_at(index),
+        });
+    }
+
+    DidDocument {
+        context: vec![
+            DID_CONTEXT.into(),
+            serde_json::json!({
+                "blockchainAccountId": BLOCKCHAIN_ACCOUNT_ID_CONTEXT,
+                "publicKeyBase58": PUBLIC_KEY_BASE58_CONTEXT,
+                ED25519_VERIFICATION_KEY: ED25519_VERIFICATION_KEY_CONTEXT,
+                SECP256K1_RECOVERY_METHOD: SECP256K1_RECOVERY_METHOD_CONTEXT,
+                BLOCKCHAIN_VERIFICATION_METHOD: BLOCKCHAIN_VERIFICATION_METHOD_CONTEXT,
+            }),
+        ],
+        id: did.clone(),
+        controller: did,
+        authentication: vec![controller_id],
+        assertion_method: verification_method.iter().map(|method| method.id.clone()).collect(),
+        verification_method,
+    }
+}
+
+fn method_type(link: &LinkedAddress) -> &'static str {
+    match link.address.namespace() {
+        AddressNamespace::Evm => SECP256K1_RECOVERY_METHOD,
+        _ => BLOCKCHAIN_VERIFICATION_METHOD,
+    }
+}
+
+// CAIP-10 id of a link. Chain agnostic EVM links are rendered on the default
+// EVM chain; other chain agnostic links keep the reserved reference.
+fn blockchain_account_id(link: &LinkedAddress, options: &ResolveOptions) -> String {
+    let namespace = link.address.namespace();
+    match (&link.chain_reference, namespace) {
+        (None, AddressNamespace::Evm) => {
+            format!("{}:{}:{}", caip2_namespace(namespace), options.default_evm_chain, link.address)
+        },
+        _ => identity_core::format_account_id(link),
+    }
+}
+
+// Verification of a link as `kind` or `kind:id`, the id being the attestor or
+// the import that vouched for it
+pub fn verification_status(verification: &LinkVerification) -> String {
+    match verification {
+        LinkVerification::SelfAsserted => "selfAsserted".to_string(),
+        LinkVerification::Attested { attestor_id } => format!("attested:{}", attestor_id),
+        LinkVerification::Downgraded { attestor_id } => format!("downgraded:{}", attestor_id),
+        LinkVerification::Imported { import_id } => format!("imported:{}", import_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::Pubkey;
+    use identity_core::{ChainAddress, LinkExpiry};
+
+    const AUTHORITY: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+
+    fn link(
+        byte: u8,
+        chain_reference: Option<&str>,
+        verification: LinkVerification,
+    ) -> LinkedAddress {
+        LinkedAddress {
+            address: ChainAddress::Evm([byte; 20]),
+            chain_reference: chain_reference.map(str::to_string),
+            verification,
+        }
+    }
+
+    // Links: 0 attested, 1 self asserted, 2 imported on chain 10 expiring at
+    // 1000, 3 downgraded
+    fn account() -> IdentityAccount {
+        IdentityAccount {
+            authority: AUTHORITY.parse::<Pubkey>().unwrap(),
+            linked_addresses: vec![
+                link(0x11, None, LinkVerification::Attested { attestor_id: 3 }),
+                link(0x22, None, LinkVerification::SelfAsserted),
+                link(0x33, Some("10"), LinkVerification::Imported { import_id: 7 }),
+                link(0x44, None, LinkVerification::Downgraded { attestor_id: 3 }),
+            ],
+            bump: 255,
+            primary: None,
+            evm_manager: None,
+            evm_nonce: 0,
+            frozen: false,
+            link_expiries: vec![LinkExpiry { index: 2, expires_at: 1000 }],
+            attestation_nonce: 0,
+            pending_links: Vec::new(),
+            open_grants: 0,
+        }
+    }
+
+    fn options(now: i64, include_unverified: bool) -> ResolveOptions {
+        ResolveOptions { now, include_unverified, ..ResolveOptions::default() }
+    }
+
+    fn method_ids(document: &DidDocument) -> Vec<String> {
+        let prefix = format!("{}#", document.id);
+        document
+            .verification_method
+            .iter()
+            .map(|method| method.id.trim_start_matches(&prefix).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn lists_only_verified_active_links() {
+        let document = resolve(&account(), &options(999, false));
+        assert_eq!(method_ids(&document), ["controller", "link-0", "link-2"]);
+        assert_eq!(document.assertion_method.len(), 3);
+        assert_eq!(document.authentication, [format!("{}#controller", document.id)]);
+    }
+
+    #[test]
+    fn leaves_out_expired_links() {
+        let document = resolve(&account(), &options(1000, false));
+        assert_eq!(method_ids(&document), ["controller", "link-0"]);
+    }
+
+    #[test]
+    fn lists_unverified_links_on_request() {
+        let document = resolve(&account(), &options(999, true));
+        assert_eq!(method_ids(&document), ["controller", "link-0", "link-1", "link-2", "link-3"]);
+        let statuses: Vec<_> = document
+            .verification_method
+            .iter()
+            .map(|method| method.verification.as_deref())
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                None,
+                Some("attested:3"),
+                Some("selfAsserted"),
+                Some("imported:7"),
+                Some("downgraded:3")
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_links_as_caip10() {
+        let document = resolve(&account(), &options(0, false));
+        assert_eq!(
+            document.id,
+            "did:pkh:solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp:7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU"
+        );
+        let controller = &document.verification_method[0];
+        assert_eq!(controller.method_type, ED25519_VERIFICATION_KEY);
+        assert_eq!(controller.public_key_base58.as_deref(), Some(AUTHORITY));
+
+        // Chain agnostic EVM links go on the default chain
+        let agnostic = &document.verification_method[1];
+        assert_eq!(agnostic.method_type, SECP256K1_RECOVERY_METHOD);
+        assert_eq!(
+            agnostic.blockchain_account_id,
+            format!("eip155:1:{}", ChainAddress::Evm([0x11; 20]))
+        );
+        let scoped = &document.verification_method[2];
+        assert_eq!(
+            scoped.blockchain_account_id,
+            format!("eip155:10:{}", ChainAddress::Evm([0x33; 20]))
+        );
+
+        let on_optimism =
+            ResolveOptions { default_evm_chain: "10".to_string(), ..options(0, false) };
+        let document = resolve(&account(), &on_optimism);
+        assert!(document.verification_method[1].blockchain_account_id.starts_with("eip155:10:"));
+    }
+
+    #[test]
+    fn serializes_verification_and_expiry() {
+        let json = serde_json::to_value(resolve(&account(), &options(0, false))).unwrap();
+        let methods = json["verificationMethod"].as_array().unwrap();
+        assert!(methods[0].get("verification").is_none());
+        assert!(methods[0].get("expires").is_none());
+        assert_eq!(methods[1]["verification"], "attested:3");
+        assert!(methods[1].get("expires").is_none());
+        assert_eq!(methods[2]["verification"], "imported:7");
+        assert_eq!(methods[2]["expires"], 1000);
+        assert_eq!(json["@context"][0], DID_CONTEXT);
+    }
+
+    #[test]
+    fn parses_cluster_names() {
+        assert_eq!(SolanaCluster::from_name("mainnet-beta"), Some(SolanaCluster::Mainnet));
+        assert_eq!(SolanaCluster::from_name("devnet"), Some(SolanaCluster::Devnet));
+        assert_eq!(SolanaCluster::from_name("localnet"), None);
+        let document = resolve(
+            &account(),
+            &ResolveOptions { cluster: SolanaCluster::Devnet, ..options(0, false) },
+        );
+        assert!(document.id.starts_with("did:pkh:solana:EtWTRABZaYq6iMfeYKouRu166VU2xqa1:"));
+    }
+}
@@ -0,0 +1,136 @@
+use anchor_lang::AccountDeserialize;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use my_oapp::state::IdentityAccount;
+use serde::Deserialize;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum DumpError {
+    // The dump is JSON but does not look like `solana account --output json`
+    InvalidJson(String),
+    // The account data is not base64 encoded
+    InvalidEncoding(String),
+    // The account data is not an IdentityAccount
+    InvalidAccount(String),
+}
+
+impl fmt::Display for DumpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DumpError::InvalidJson(err) => write!(f, "invalid account JSON: {}", err),
+            DumpError::InvalidEncoding(err) => write!(f, "invalid account data encoding: {}", err),
+            DumpError::InvalidAccount(err) => write!(f, "not an identity account: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for DumpError {}
+
+// Shape of `solana account <pubkey> --output json`
+#[derive(Deserialize)]
+struct JsonDump {
+    account: JsonAccount,
+}
+
+#[derive(Deserialize)]
+struct JsonAccount {
+    // [payload, encoding]
+    data: (String, String),
+}
+
+// Load an IdentityAccount from an account dump. Both the JSON output of
+// `solana account <pubkey> --output json` and the raw account data written by
+// `solana account <pubkey> --output-file <file>` are accepted.
+pub fn load_identity_account(dump: &[u8]) -> Result<IdentityAccount, DumpError> {
+    let data = if dump.first() == Some(&b'{') {
+        let json: JsonDump =
+            serde_json::from_slice(dump).map_err(|err| DumpError::InvalidJson(err.to_string()))?;
+        let (payload, encoding) = json.account.data;
+        if encoding != "base64" {
+            return Err(DumpError::InvalidEncoding(encoding));
+        }
+        STANDARD
+            .decode(payload)
+            .map_err(|err| DumpError::InvalidEncoding(err.to_string()))?
+    } else {
+        dump.to_vec()
+    };
+
+    // Checks the account discriminator before decoding the fields
+    IdentityAccount::try_deserialize(&mut data.as_slice())
+        .map_err(|err| DumpError::InvalidAccount(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{prelude::Pubkey, AccountSerialize};
+    use identity_core::{ChainAddress, LinkedAddress};
+
+    fn account_data() -> Vec<u8> {
+        let account = IdentityAccount {
+            authority: Pubkey::new_from_array([7; 32]),
+            linked_addresses: vec![LinkedAddress::new(ChainAddress::Evm([0x11; 20]), None)],
+            bump: 254,
+            primary: Some(0),
+            evm_manager: None,
+            evm_nonce: 4,
+            frozen: false,
+            link_expiries: Vec::new(),
+            attestation_nonce: 2,
+            pending_links: Vec::new(),
+            open_grants: 0,
+        };
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        // Accounts are allocated at full size, the rest is zeroes
+        data.resize(IdentityAccount::SIZE, 0);
+        data
+    }
+
+    fn json_dump(data: &str, encoding: &str) -> Vec<u8> {
+        serde_json::json!({
+            "pubkey": "11111111111111111111111111111111",
+            "account": { "lamports": 1, "data": [data, encoding], "owner": "x", "executable": false },
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    #[test]
+    fn loads_raw_account_data() {
+        let account = load_identity_account(&account_data()).unwrap();
+        assert_eq!(account.authority, Pubkey::new_from_array([7; 32]));
+        assert_eq!(account.linked_addresses.len(), 1);
+        assert_eq!(account.primary, Some(0));
+        assert_eq!(account.evm_nonce, 4);
+        assert_eq!(account.attestation_nonce, 2);
+    }
+
+    #[test]
+    fn loads_json_dumps() {
+        let dump = json_dump(&STANDARD.encode(account_data()), "base64");
+        let account = load_identity_account(&dump).unwrap();
+        assert_eq!(account.bump, 254);
+    }
+
+    #[test]
+    fn rejects_other_encodings() {
+        let dump = json_dump("abc", "base58");
+        assert!(matches!(load_identity_account(&dump), Err(DumpError::InvalidEncoding(_))));
+        let dump = json_dump("not base64!", "base64");
+        assert!(matches!(load_identity_account(&dump), Err(DumpError::InvalidEncoding(_))));
+        assert!(matches!(
+            load_identity_account(b"{\"account\": 1}"),
+            Err(DumpError::InvalidJson(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_other_accounts() {
+        let mut data = account_data();
+        data[0] ^= 0xff;
+        assert!(matches!(load_identity_account(&data), Err(DumpError::InvalidAccount(_))));
+        assert!(matches!(load_identity_account(&[]), Err(DumpError::InvalidAccount(_))));
+    }
+}
@@ -0,0 +1,16 @@
+// -----------------------------------------------------------------------------
+// Offline resolver that turns a `my_oapp` IdentityAccount into a W3C DID
+// document using the did:pkh method:
+//
+//   did:pkh:solana:<genesis hash prefix>:<authority>
+//
+// The Solana authority is the controller of the document, and every linked
+// address becomes a verification method identified by its CAIP-10
+// `blockchainAccountId`. Only an account dump is needed, no RPC.
+// -----------------------------------------------------------------------------
+
+mod did;
+mod dump;
+
+pub use did::*;
+pub use dump::*;
@@ -0,0 +1,52 @@
+use identity_resolver::{load_identity_account, resolve, ResolveOptions, SolanaCluster};
+use std::{env, fs, process};
+
+const USAGE: &str = "usage: identity-resolver <account-dump> [--cluster mainnet|devnet|testnet] [--evm-chain <chain id>]
+                         [--at <unix timestamp>] [--include-unverified]
+
+Reads an identity account dumped with `solana account <identity PDA> --output json`
+(or `--output-file`) and prints its did:pkh DID document. Links expired at `--at`,
+by default now, are left out, as are links no one verified unless
+`--include-unverified` is given.";
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = env::args().skip(1);
+    let mut dump_path = None;
+    let mut options = ResolveOptions::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--cluster" => {
+                let name = args.next().ok_or("--cluster needs a value")?;
+                options.cluster = SolanaCluster::from_name(&name)
+                    .ok_or_else(|| format!("unknown cluster {}", name))?;
+            },
+            "--evm-chain" => {
+                options.default_evm_chain = args.next().ok_or("--evm-chain needs a value")?;
+            },
+            "--at" => {
+                let at = args.next().ok_or("--at needs a value")?;
+                options.now = at.parse().map_err(|_| format!("invalid timestamp {}", at))?;
+            },
+            "--include-unverified" => options.include_unverified = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            },
+            path if dump_path.is_none() => dump_path = Some(path.to_string()),
+            other => return Err(format!("unexpected argument {}\n\n{}", other, USAGE).into()),
+        }
+    }
+
+    let dump_path = dump_path.ok_or(USAGE)?;
+    let account = load_identity_account(&fs::read(dump_path)?)?;
+    println!("{}", serde_json::to_string_pretty(&resolve(&account, &options))?);
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use identity_core::{ExecutionOptions, OptionsBuilder};
+use std::{env, process};
+
+const USAGE: &str = "usage: lz-options decode <hex options>
+       lz-options build [--lz-receive <gas>[,<value>]] [--native-drop <amount>,<receiver hex>]
+                        [--lz-compose <index>,<gas>[,<value>]] [--ordered-execution]
+
+Builds LayerZero type 3 execution options as hex, or decodes them into one line per option.
+Options can be repeated and are encoded in the order given.";
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("decode") => {
+            let hex = args.next().ok_or(USAGE)?;
+            println!("{}", ExecutionOptions::decode(&parse_hex(&hex)?)?);
+        },
+        Some("build") => {
+            let mut builder = OptionsBuilder::new();
+            while let Some(arg) = args.next() {
+                builder = match arg.as_str() {
+                    "--ordered-execution" => builder.ordered_execution(),
+                    flag => {
+                        let value = args.next().ok_or_else(|| format!("{} needs a value", flag))?;
+                        let parts: Vec<&str> = value.split(',').collect();
+                        match (flag, parts.as_slice()) {
+                            ("--lz-receive", [gas]) => builder.lz_receive(gas.parse()?, 0),
+                            ("--lz-receive", [gas, value]) => {
+                                builder.lz_receive(gas.parse()?, value.parse()?)
+                            },
+                            ("--native-drop", [amount, receiver]) => {
+                                builder.native_drop(amount.parse()?, parse_receiver(receiver)?)
+                            },
+                            ("--lz-compose", [index, gas]) => {
+                                builder.lz_compose(index.parse()?, gas.parse()?, 0)
+                            },
+                            ("--lz-compose", [index, gas, value]) => {
+                                builder.lz_compose(index.parse()?, gas.parse()?, value.parse()?)
+                            },
+                            _ => {
+                                return Err(
+                                    format!("unexpected argument {}\n\n{}", flag, USAGE).into()
+                                )
+                            },
+                        }
+                    },
+                };
+            }
+            let options: String = builder.build().iter().map(|b| format!("{:02x}", b)).collect();
+            println!("0x{}", options);
+        },
+        Some("-h") | Some("--help") => println!("{}", USAGE),
+        _ => return Err(USAGE.into()),
+    }
+    Ok(())
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.len() % 2 != 0 {
+        return Err(format!("odd length hex {}", hex));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| format!("invalid hex {}", hex)))
+        .collect()
+}
+
+// Native drop receivers are bytes32, so EVM addresses are left padded
+fn parse_receiver(hex: &str) -> Result<[u8; 32], String> {
+    let bytes = parse_hex(hex)?;
+    if bytes.len() > 32 {
+        return Err(format!("receiver {} is longer than 32 bytes", hex));
+    }
+    let mut receiver = [0u8; 32];
+    receiver[32 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(receiver)
+}